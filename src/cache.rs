@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+type Place = (f64, f64, String);
+
+fn dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+    Some(base.join("weather"))
+}
+
+fn key(city: &str) -> String {
+    city.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn load_places() -> HashMap<String, Place> {
+    dir()
+        .and_then(|d| std::fs::read_to_string(d.join("geocode.json")).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn place(city: &str) -> Option<Place> {
+    load_places().remove(&key(city))
+}
+
+pub fn store_place(city: &str, place: &Place) {
+    let Some(d) = dir() else { return };
+    let mut places = load_places();
+    places.insert(key(city), place.clone());
+    if std::fs::create_dir_all(&d).is_ok()
+        && let Ok(s) = serde_json::to_string(&places)
+    {
+        let _ = std::fs::write(d.join("geocode.json"), s);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Forecast {
+    fetched: i64,
+    #[serde(default)]
+    until: String,
+    #[serde(default)]
+    tz: Option<String>,
+    body: String,
+}
+
+fn forecast_file(lat: f64, lon: f64) -> Option<PathBuf> {
+    Some(dir()?.join(format!("weather-{lat:.4}_{lon:.4}.json")))
+}
+
+// A body is only reused for the same response timezone and when its window reaches the requested last day
+pub fn forecast(lat: f64, lon: f64, until: Option<&str>, tz: Option<&str>) -> Option<(String, i64)> {
+    let s = std::fs::read_to_string(forecast_file(lat, lon)?).ok()?;
+    let f: Forecast = serde_json::from_str(&s).ok()?;
+    if f.tz.as_deref() != tz || until.is_some_and(|u| f.until.get(..10) < u.get(..10)) {
+        return None;
+    }
+    let age = chrono::Utc::now().timestamp() - f.fetched;
+    Some((f.body, age.max(0) / 60))
+}
+
+pub fn store_forecast(lat: f64, lon: f64, until: &str, tz: Option<&str>, body: &str) {
+    let (Some(d), Some(path)) = (dir(), forecast_file(lat, lon)) else { return };
+    let f = Forecast {
+        fetched: chrono::Utc::now().timestamp(),
+        until: until.to_string(),
+        tz: tz.map(str::to_string),
+        body: body.to_string(),
+    };
+    if std::fs::create_dir_all(d).is_ok()
+        && let Ok(s) = serde_json::to_string(&f)
+    {
+        let _ = std::fs::write(path, s);
+    }
+}
+
+pub type DayStats = (f64, f64, f64);
+
+fn summary_file(lat: f64, lon: f64) -> Option<PathBuf> {
+    Some(dir()?.join(format!("summary-{lat:.4}_{lon:.4}.json")))
+}
+
+pub fn summary(lat: f64, lon: f64) -> Option<HashMap<String, DayStats>> {
+    let s = std::fs::read_to_string(summary_file(lat, lon)?).ok()?;
+    serde_json::from_str(&s).ok()
+}
+
+pub fn store_summary(lat: f64, lon: f64, days: &HashMap<String, DayStats>) {
+    let (Some(d), Some(path)) = (dir(), summary_file(lat, lon)) else { return };
+    if std::fs::create_dir_all(d).is_ok()
+        && let Ok(s) = serde_json::to_string(days)
+    {
+        let _ = std::fs::write(path, s);
+    }
+}
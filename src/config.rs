@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub city: Option<String>,
+    pub default_coords: Option<[f64; 2]>,
+    pub lang: Option<String>,
+    pub wind_scale: Option<String>,
+    pub units: Option<String>,
+    pub icons: HashMap<String, String>,
+}
+
+fn path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("weather").join("config.toml"))
+}
+
+pub fn parse(path: &Path, text: &str) -> (Config, Option<String>) {
+    match toml::from_str(text) {
+        Ok(c) => (c, None),
+        Err(e) => {
+            let detail = match e.span() {
+                Some(span) => format!("line {}: {}", text[..span.start].lines().count().max(1), e.message()),
+                None => e.message().to_string(),
+            };
+            (Config::default(), Some(format!("Config error in {}: {detail}", path.display())))
+        }
+    }
+}
+
+pub fn load() -> Config {
+    let Some(path) = path() else { return Config::default() };
+    let Ok(text) = std::fs::read_to_string(&path) else { return Config::default() };
+    let (config, warning) = parse(&path, &text);
+    if let Some(w) = warning {
+        eprintln!("{w}");
+    }
+    config
+}
@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+use ureq::ResponseExt;
+
+const MAX_REDIRECTS: u32 = 5;
+
+#[derive(Debug)]
+pub struct FetchError {
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    detail: Option<serde_json::Value>,
+    message: Option<String>,
+    error: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    fn text(&self) -> Option<String> {
+        match &self.detail {
+            Some(serde_json::Value::String(s)) => return Some(s.clone()),
+            Some(serde_json::Value::Array(items)) => {
+                let msgs: Vec<&str> = items.iter().filter_map(|i| i["msg"].as_str()).collect();
+                if !msgs.is_empty() {
+                    return Some(msgs.join("; "));
+                }
+            }
+            _ => {}
+        }
+        match &self.error {
+            Some(serde_json::Value::String(s)) => Some(s.clone()),
+            Some(e) => e["message"].as_str().map(str::to_string).or(self.message.clone()),
+            None => self.message.clone(),
+        }
+    }
+}
+
+fn host(url: &str) -> &str {
+    url.split('/').nth(2).unwrap_or(url)
+}
+
+fn describe(e: &ureq::Error, url: &str) -> String {
+    match e {
+        ureq::Error::Io(io) if io.to_string().contains("lookup address") || io.to_string().contains("No such host") => {
+            format!("Could not resolve host {}; check your internet connection", host(url))
+        }
+        ureq::Error::HostNotFound => format!("Could not resolve host {}; check your internet connection", host(url)),
+        ureq::Error::Tls(_) | ureq::Error::Rustls(_) | ureq::Error::TlsRequired => {
+            format!("Secure connection to {} failed ({e}); check the system clock or any TLS-intercepting proxy", host(url))
+        }
+        ureq::Error::Io(io) if io.kind() == std::io::ErrorKind::ConnectionRefused => {
+            format!("Connection refused by {}; if this is a self-hosted endpoint, check that it is running", host(url))
+        }
+        ureq::Error::ConnectionFailed => format!("Could not connect to {}; check your internet connection", host(url)),
+        ureq::Error::Timeout(_) => format!("Request to {} timed out", host(url)),
+        _ => format!("Error: {e}"),
+    }
+}
+
+pub trait HttpClient {
+    fn get(&self, url: &str) -> Result<String, FetchError>;
+}
+
+#[derive(Serialize)]
+pub struct Exchange {
+    url: String,
+    status: Option<u16>,
+    bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+pub struct UreqClient {
+    verbose: bool,
+    keep_bodies: bool,
+    exchanges: RefCell<Vec<Exchange>>,
+}
+
+impl UreqClient {
+    pub fn new(verbose: bool, keep_bodies: bool) -> UreqClient {
+        UreqClient { verbose, keep_bodies, exchanges: RefCell::new(Vec::new()) }
+    }
+
+    pub fn exchanges(&self) -> std::cell::Ref<'_, Vec<Exchange>> {
+        self.exchanges.borrow()
+    }
+
+    pub fn clear(&self) {
+        self.exchanges.borrow_mut().clear();
+    }
+
+    fn record(&self, url: &str, status: Option<u16>, body: &str) {
+        self.exchanges.borrow_mut().push(Exchange {
+            url: url.to_string(),
+            status,
+            bytes: body.len(),
+            body: self.keep_bodies.then(|| body.to_string()),
+        });
+    }
+}
+
+impl HttpClient for UreqClient {
+    fn get(&self, url: &str) -> Result<String, FetchError> {
+        let mut http = match ureq::get(url)
+            .header("User-Agent", "weather-cli")
+            .config()
+            .http_status_as_error(false)
+            .max_redirects(MAX_REDIRECTS)
+            .save_redirect_history(true)
+            .build()
+            .call()
+        {
+            Ok(r) => r,
+            Err(e) => {
+                self.record(url, None, "");
+                return Err(FetchError { status: None, message: describe(&e, url) });
+            }
+        };
+        if self.verbose && let Some(history) = http.get_redirect_history() {
+            for hop in history.windows(2) {
+                eprintln!("Redirected: {} → {}", hop[0], hop[1]);
+            }
+        }
+        let status = http.status();
+        let body = http.body_mut().read_to_string().unwrap_or_default();
+        self.record(url, Some(status.as_u16()), &body);
+        if !status.is_success() {
+            let detail = serde_json::from_str::<ApiError>(&body).ok().and_then(|e| e.text());
+            let message = match detail {
+                Some(msg) => format!("Error from {} ({}): {msg}", host(url), status.as_u16()),
+                None => format!("Error: HTTP {status}"),
+            };
+            return Err(FetchError { status: Some(status.as_u16()), message });
+        }
+        Ok(body)
+    }
+}
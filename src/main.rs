@@ -1,5 +1,21 @@
-use chrono::{Local, NaiveDate};
-use serde::Deserialize;
+mod cache;
+mod config;
+mod http;
+mod tui;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+
+use http::{FetchError, HttpClient, UreqClient};
 
 const BOLD: &str = "\x1b[1m";
 const DIM: &str = "\x1b[2m";
@@ -8,27 +24,901 @@ const BLUE: &str = "\x1b[34m";
 const GREEN: &str = "\x1b[32m";
 const YELLOW: &str = "\x1b[33m";
 const RED: &str = "\x1b[31m";
+const GRAY: &str = "\x1b[90m";
+const MAGENTA: &str = "\x1b[35m";
+const BRIGHT_YELLOW: &str = "\x1b[93m";
+const RAIN_BG: &str = "\x1b[48;5;17m";
 const RESET: &str = "\x1b[0m";
 
+static COLOR: AtomicBool = AtomicBool::new(true);
+static TEXT_GLYPHS: AtomicBool = AtomicBool::new(false);
+static PRINTED: AtomicUsize = AtomicUsize::new(0);
+static STDOUT_CLOSED: AtomicBool = AtomicBool::new(false);
+static MAX_WIDTH: AtomicUsize = AtomicUsize::new(usize::MAX);
+static ICONS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+thread_local! {
+    static SINK: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+macro_rules! out {
+    () => { emit("") };
+    ($($arg:tt)*) => { emit(&format!($($arg)*)) };
+}
+
+// Legacy Windows consoles print escapes literally unless virtual terminal processing can be enabled
+#[cfg(windows)]
+fn ansi_supported() -> bool {
+    crossterm::ansi_support::supports_ansi()
+}
+
+#[cfg(not(windows))]
+fn ansi_supported() -> bool {
+    true
+}
+
+fn strip_ansi(s: &str) -> String {
+    let mut plain = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                chars.by_ref().find(|c| ('@'..='~').contains(c));
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next().is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    plain
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0xFE0E | 0xFE0F | 0x200D => 0,
+        0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x1F000.. => 2,
+        _ => 1,
+    }
+}
+
+fn display_width(s: &str) -> usize {
+    strip_ansi(s).chars().map(char_width).sum()
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut used = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        used += char_width(c);
+        if used > width {
+            out.push_str(RESET);
+            break;
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn emit(line: &str) {
+    let width = MAX_WIDTH.load(Ordering::Relaxed);
+    let line = if display_width(line) > width { truncate(line, width) } else { line.to_string() };
+    let line = if COLOR.load(Ordering::Relaxed) { line } else { strip_ansi(&line) };
+    // Without U+FE0F the glyphs fall back to one-column text style, the space keeps columns aligned
+    let line = if TEXT_GLYPHS.load(Ordering::Relaxed) { line.replace('\u{fe0f}', " ") } else { line };
+    emit_raw(&line);
+}
+
+// Machine-readable output (JSON) must reach the reader byte for byte, no width cap or glyph rewriting
+fn emit_raw(line: &str) {
+    let captured = SINK.with_borrow_mut(|sink| match sink {
+        Some(buf) => {
+            buf.push_str(line);
+            buf.push('\n');
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        PRINTED.fetch_add(1, Ordering::Relaxed);
+        write_stdout(&format!("{line}\n"));
+    }
+}
+
+// A closed reader (`weather | head`) is not an error: drop further output quietly, file sinks still get written
+fn write_stdout(text: &str) {
+    use std::io::Write;
+    if STDOUT_CLOSED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut stdout = std::io::stdout().lock();
+    if let Err(e) = stdout.write_all(text.as_bytes()).and_then(|_| stdout.flush()) {
+        if e.kind() == std::io::ErrorKind::BrokenPipe {
+            STDOUT_CLOSED.store(true, Ordering::Relaxed);
+            return;
+        }
+        eprintln!("Error writing output: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn capture(f: impl FnOnce()) -> String {
+    let outer = SINK.replace(Some(String::new()));
+    f();
+    SINK.replace(outer).unwrap_or_default()
+}
+
+fn page(text: &str) {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let pager = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()).unwrap_or_else(|| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let spawned = Command::new(parts.next().unwrap_or("less")).args(parts).stdin(Stdio::piped()).spawn();
+    match spawned {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let _ = child.wait();
+        }
+        Err(e) => {
+            eprintln!("Warning: could not start pager {pager} ({e})");
+            write_stdout(text);
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct Response {
     weather: Vec<WeatherEntry>,
+    #[serde(default)]
+    sources: Vec<Source>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Source {
+    id: i64,
+    lat: f64,
+    lon: f64,
+    dwd_station_id: Option<String>,
+    wmo_station_id: Option<String>,
+    station_name: Option<String>,
+    distance: Option<f64>,
+}
+
+fn lowercase<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(d)?.map(|s| s.to_lowercase()))
 }
 
 #[derive(Deserialize)]
 struct WeatherEntry {
     timestamp: String,
-    temperature: f64,
+    temperature: Option<f64>,
     precipitation_probability: Option<f64>,
-    condition: String,
+    #[serde(default, deserialize_with = "lowercase")]
+    condition: Option<String>,
+    wind_speed: Option<f64>,
+    precipitation: Option<f64>,
+    cloud_cover: Option<f64>,
+    relative_humidity: Option<f64>,
+    visibility: Option<f64>,
+    thunderstorm_probability: Option<f64>,
+    source_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CurrentResponse {
+    weather: CurrentWeather,
+}
+
+#[derive(Deserialize)]
+struct CurrentWeather {
+    timestamp: String,
+    temperature: Option<f64>,
+    #[serde(default, deserialize_with = "lowercase")]
+    condition: Option<String>,
+    wind_speed_10: Option<f64>,
+    precipitation_60: Option<f64>,
+    cloud_cover: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct AlertsResponse {
+    alerts: Vec<Alert>,
+}
+
+#[derive(Deserialize)]
+struct Alert {
+    severity: String,
+    headline_en: Option<String>,
+    headline_de: Option<String>,
+    onset: Option<String>,
+    expires: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct Hour {
+    time: String,
+    temp: f64,
+    rp: f64,
+    cond: String,
+    wind: Option<f64>,
+    precip: Option<f64>,
+    cloud: Option<f64>,
+    visibility: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storm: Option<f64>,
+    #[serde(skip)]
+    utc: Option<NaiveDateTime>,
 }
 
 struct DaySummary {
     hi: f64,
     lo: f64,
     max_rp: f64,
+    max_wind: Option<f64>,
     conds: Vec<String>,
-    hours: Vec<(String, f64, f64, String)>,
+    cond_hours: HashMap<String, u32>,
+    clouds: Vec<f64>,
+    humidity: Vec<f64>,
+    temps: Vec<f64>,
+    rain_hours: u32,
+    anomaly: Option<f64>,
+    dst_note: Option<String>,
+    sun: Option<(NaiveDateTime, NaiveDateTime)>,
+    hours: Vec<Hour>,
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn median(vals: &mut [f64]) -> f64 {
+    vals.sort_by(f64::total_cmp);
+    let mid = vals.len() / 2;
+    match vals.len() {
+        0 => f64::NAN,
+        n if n % 2 == 0 => (vals[mid - 1] + vals[mid]) / 2.0,
+        _ => vals[mid],
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Aggregate {
+    Mean,
+    Median,
+}
+
+impl Aggregate {
+    fn parse(s: &str) -> Option<Aggregate> {
+        match s {
+            "mean" => Some(Aggregate::Mean),
+            "median" => Some(Aggregate::Median),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Lang {
+    #[default]
+    En,
+    De,
+}
+
+impl Lang {
+    fn parse(s: &str) -> Option<Lang> {
+        match s.to_lowercase().as_str() {
+            "en" => Some(Lang::En),
+            "de" => Some(Lang::De),
+            _ => None,
+        }
+    }
+
+    fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|k| std::env::var(k).ok())
+            .find(|v| !v.is_empty())
+            .and_then(|v| Lang::parse(v.get(..2).unwrap_or("")))
+            .unwrap_or(Lang::En)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    fn parse(s: &str) -> Option<Units> {
+        match s.to_lowercase().as_str() {
+            "metric" | "c" | "celsius" => Some(Units::Metric),
+            "imperial" | "f" | "fahrenheit" => Some(Units::Imperial),
+            _ => None,
+        }
+    }
+}
+
+fn detect_units_from_locale() -> Units {
+    let imperial = ["LC_ALL", "LC_MEASUREMENT", "LANG"]
+        .iter()
+        .filter_map(|k| std::env::var(k).ok())
+        .find(|v| !v.is_empty())
+        .is_some_and(|v| ["en_US", "en_LR", "my_MM"].iter().any(|l| v.starts_with(l)));
+    if imperial { Units::Imperial } else { Units::Metric }
+}
+
+#[derive(Clone, Copy, Default)]
+enum WindScale {
+    #[default]
+    Kmh,
+    Ms,
+    Beaufort,
+}
+
+impl WindScale {
+    fn parse(s: &str) -> Option<WindScale> {
+        match s {
+            "kmh" => Some(WindScale::Kmh),
+            "ms" => Some(WindScale::Ms),
+            "beaufort" => Some(WindScale::Beaufort),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+enum SortKey {
+    #[default]
+    Date,
+    Hi,
+    Rain,
+}
+
+impl SortKey {
+    fn parse(s: &str) -> Option<SortKey> {
+        match s {
+            "date" => Some(SortKey::Date),
+            "hi" => Some(SortKey::Hi),
+            "rain" => Some(SortKey::Rain),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Format> {
+        match s {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum ColorBy {
+    #[default]
+    Temp,
+    Condition,
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum LayoutStyle {
+    #[default]
+    Normal,
+    Wide,
+}
+
+#[derive(Default)]
+struct Options {
+    city: String,
+    lang: Lang,
+    refresh_geocode: bool,
+    verbose: bool,
+    wind_scale: WindScale,
+    explain: bool,
+    now: bool,
+    max_age: Option<i64>,
+    offline: bool,
+    no_prompt: bool,
+    sort: SortKey,
+    overview: bool,
+    layout: LayoutStyle,
+    delta: bool,
+    format: Format,
+    color_by: ColorBy,
+    file: Option<String>,
+    max_entries: Option<usize>,
+    diff: bool,
+    decimal_comma: bool,
+    alerts: bool,
+    strict: bool,
+    timezone: Option<Tz>,
+    notify: bool,
+    plain_text: bool,
+    exact_minutes: bool,
+    today_only: bool,
+    color_256: bool,
+    log: Option<String>,
+    log_bodies: bool,
+    breakdown: bool,
+    no_color: bool,
+    prefetch: bool,
+    shade_rain: Option<f64>,
+    min_temp: Option<f64>,
+    interval_summary: bool,
+    brief: bool,
+    show_coords: bool,
+    max_width: Option<usize>,
+    collapse: bool,
+    stdin_json: bool,
+    comfort: bool,
+    pager: bool,
+    tui: bool,
+    relative_rain: bool,
+    auto_locate: bool,
+    no_today_label: bool,
+    sample_interval: Option<usize>,
+    max_station_distance: Option<f64>,
+    response_tz: Option<Tz>,
+    daylight: bool,
+    group_by_week: bool,
+    legend: bool,
+    round_temp_to: Option<f64>,
+    status_line: bool,
+    summary: bool,
+    highlight_now: bool,
+    hour_range: Option<(u32, u32)>,
+    export_ics: Option<String>,
+    rain_hours: bool,
+    today_first: bool,
+    anomaly: bool,
+    strip_ansi: bool,
+    benchmark: bool,
+    compact_json: bool,
+    max_days_from_now: Option<i64>,
+    days: Option<i64>,
+    units: Units,
+    condition_stats: bool,
+    prob_as_fraction: bool,
+    location_codes: bool,
+    no_variation_selector: bool,
+    aggregate: Option<Aggregate>,
+    limit_rows: Option<usize>,
+    dayparts: Option<&'static [&'static str]>,
+    seed_cache: Option<String>,
+    emit_metrics: Option<String>,
+    width_per_hour: Option<usize>,
+    output_json: Option<String>,
+    accum: bool,
+    condition_override: Option<String>,
+    temp_override: Option<f64>,
+    rain_override: Option<f64>,
+    watch: Option<u64>,
+    refresh: Option<(u32, u64)>,
+    describe: Option<String>,
+    label: Option<String>,
+    max_temp: Option<f64>,
+    force_color: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let cfg = config::load();
+    let mut opts = Options { lang: Lang::from_env(), units: detect_units_from_locale(), ..Default::default() };
+    if let Some(v) = &cfg.lang {
+        opts.lang = Lang::parse(v).ok_or(format!("Unknown language in config: {v}"))?;
+    }
+    for name in cfg.icons.keys().filter(|k| !CONDITIONS.contains(&k.as_str())) {
+        eprintln!("Ignoring icon for unknown condition: {name}");
+    }
+    let _ = ICONS.set(cfg.icons.clone());
+    if let Some(v) = &cfg.units {
+        opts.units = Units::parse(v).ok_or(format!("Unknown units in config: {v}"))?;
+    }
+    if let Some(v) = &cfg.wind_scale {
+        opts.wind_scale = WindScale::parse(v).ok_or(format!("Unknown wind scale in config: {v}"))?;
+    }
+    let mut words = Vec::new();
+    let mut decimal_sep = None;
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let mut profile = None;
+    if let Some(i) = argv.iter().position(|a| a == "--profile") {
+        let v = argv.get(i + 1).ok_or("--profile needs outdoor, commute or garden")?;
+        apply_profile(&mut opts, v)?;
+        profile = Some(v.clone());
+    }
+    let mut args = argv.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lang" => {
+                let v = args.next().ok_or("--lang needs a value")?;
+                opts.lang = Lang::parse(&v).ok_or(format!("Unknown language: {v}"))?;
+            }
+            "--refresh-geocode" => opts.refresh_geocode = true,
+            "-v" | "--verbose" => opts.verbose = true,
+            "--explain" => opts.explain = true,
+            "--now" => opts.now = true,
+            "--offline" => opts.offline = true,
+            "--no-prompt" => opts.no_prompt = true,
+            "-h" | "--help" => {
+                write_stdout(HELP);
+                std::process::exit(0);
+            }
+            "--overview" => opts.overview = true,
+            "--diff" => opts.diff = true,
+            "--alerts" => opts.alerts = true,
+            "--strict" => opts.strict = true,
+            "--notify" => opts.notify = true,
+            "--today-only" => opts.today_only = true,
+            "--log" => opts.log = Some(args.next().ok_or("--log needs a value")?),
+            "--log-bodies" => opts.log_bodies = true,
+            "--breakdown" => opts.breakdown = true,
+            "--no-color" => opts.no_color = true,
+            "--prefetch" => opts.prefetch = true,
+            "--interval-summary" => opts.interval_summary = true,
+            "--brief" => opts.brief = true,
+            "--show-coords" => opts.show_coords = true,
+            "--collapse" => opts.collapse = true,
+            "--stdin-json" => opts.stdin_json = true,
+            "--comfort" => opts.comfort = true,
+            "--pager" => opts.pager = true,
+            "--tui" => opts.tui = true,
+            "--relative-rain" => opts.relative_rain = true,
+            "--auto-locate" => opts.auto_locate = true,
+            "--no-today-label" => opts.no_today_label = true,
+            "--max-station-distance" => {
+                let v = args.next().ok_or("--max-station-distance needs a value")?;
+                opts.max_station_distance = Some(v.parse().map_err(|_| format!("Invalid --max-station-distance: {v}"))?);
+            }
+            "--sample-interval" => {
+                let v = args.next().ok_or("--sample-interval needs a value")?;
+                let n = v.parse().ok().filter(|n| *n > 0).ok_or(format!("Invalid --sample-interval: {v}"))?;
+                opts.sample_interval = Some(n);
+            }
+            "--label" => opts.label = Some(args.next().ok_or("--label needs a value")?),
+            "--max-width" => {
+                let v = args.next().ok_or("--max-width needs a value")?;
+                opts.max_width = Some(v.parse().map_err(|_| format!("Invalid --max-width: {v}"))?);
+            }
+            "--coords" => words.push(args.next().ok_or("--coords needs a value")?),
+            "--min-temp" => {
+                let v = args.next().ok_or("--min-temp needs a value")?;
+                opts.min_temp = Some(v.parse().map_err(|_| format!("Invalid --min-temp: {v}"))?);
+            }
+            "--max-temp" => {
+                let v = args.next().ok_or("--max-temp needs a value")?;
+                opts.max_temp = Some(v.parse().map_err(|_| format!("Invalid --max-temp: {v}"))?);
+            }
+            "--shade-rain" => {
+                let v = args.next().ok_or("--shade-rain needs a value")?;
+                opts.shade_rain = Some(v.trim_end_matches('%').parse().map_err(|_| format!("Invalid --shade-rain: {v}"))?);
+            }
+            "--force-color" => opts.force_color = true,
+            "--color-mode" => {
+                let v = args.next().ok_or("--color-mode needs a value")?;
+                opts.color_256 = match v.as_str() {
+                    "16" => false,
+                    "256" => true,
+                    _ => return Err(format!("Unknown color mode: {v}")),
+                };
+            }
+            "--hour-labels" => {
+                let v = args.next().ok_or("--hour-labels needs a value")?;
+                opts.exact_minutes = match v.as_str() {
+                    "bucket" => false,
+                    "exact" => true,
+                    _ => return Err(format!("Unknown hour label mode: {v}")),
+                };
+            }
+            "--screenreader" | "--plain-text" => opts.plain_text = true,
+            "--timezone" => {
+                let v = args.next().ok_or("--timezone needs a value")?;
+                opts.timezone = Some(v.parse().map_err(|_| format!("Unknown timezone: {v}"))?);
+            }
+            "--daylight" => opts.daylight = true,
+            "--legend" => opts.legend = true,
+            "--status-line" => opts.status_line = true,
+            "--summary" => opts.summary = true,
+            "--highlight-now" => opts.highlight_now = true,
+            "--rain-hours" => opts.rain_hours = true,
+            "--today-first" => opts.today_first = true,
+            "--anomaly" => opts.anomaly = true,
+            "--strip-ansi" => opts.strip_ansi = true,
+            "--benchmark" => opts.benchmark = true,
+            "--compact-json" => opts.compact_json = true,
+            "--condition-stats" => opts.condition_stats = true,
+            "--prob-as-fraction" => opts.prob_as_fraction = true,
+            "--location-codes" => opts.location_codes = true,
+            "--no-emoji-variation-selector" => opts.no_variation_selector = true,
+            "--watch" => {
+                let v = args.next().ok_or("--watch needs a value")?;
+                opts.watch = Some(v.parse().map_err(|_| format!("Invalid --watch: {v}"))?);
+            }
+            "--refresh" => {
+                let v = args.next().ok_or("--refresh needs a value")?;
+                let parsed = v.split_once(',').and_then(|(n, secs)| Some((n.trim().parse().ok()?, secs.trim().parse().ok()?)));
+                opts.refresh = Some(parsed.filter(|(n, _)| *n > 0).ok_or(format!("Invalid --refresh: {v} (expected N,SECONDS)"))?);
+            }
+            "--round-temp-to" => {
+                let v = args.next().ok_or("--round-temp-to needs a value")?;
+                let step = v.parse().ok().filter(|s: &f64| *s > 0.0).ok_or(format!("Invalid --round-temp-to: {v}"))?;
+                opts.round_temp_to = Some(step);
+            }
+            "--describe-condition" => {
+                let v = args.next().ok_or("--describe-condition needs a value")?.to_lowercase();
+                if !CONDITIONS.contains(&v.as_str()) {
+                    return Err(format!("Unknown condition: {v} (known: {})", CONDITIONS.join(", ")));
+                }
+                opts.describe = Some(v);
+            }
+            "--group-by" => {
+                let v = args.next().ok_or("--group-by needs a value")?;
+                opts.group_by_week = match v.as_str() {
+                    "week" => true,
+                    "none" => false,
+                    _ => return Err(format!("Unknown grouping: {v}")),
+                };
+            }
+            "--response-tz" => {
+                let v = args.next().ok_or("--response-tz needs a value")?;
+                opts.response_tz = Some(v.parse().map_err(|_| format!("Unknown timezone: {v}"))?);
+            }
+            "--decimal-sep" => {
+                let v = args.next().ok_or("--decimal-sep needs a value")?;
+                decimal_sep = match v.as_str() {
+                    "," | "comma" => Some(true),
+                    "." | "dot" => Some(false),
+                    _ => return Err(format!("Unknown decimal separator: {v}")),
+                };
+            }
+            "--delta" => opts.delta = true,
+            "--color-by" => {
+                let v = args.next().ok_or("--color-by needs a value")?;
+                opts.color_by = match v.as_str() {
+                    "temp" => ColorBy::Temp,
+                    "condition" => ColorBy::Condition,
+                    _ => return Err(format!("Unknown color scheme: {v}")),
+                };
+            }
+            "--condition-colors" => opts.color_by = ColorBy::Condition,
+            "--max-entries" => {
+                let v = args.next().ok_or("--max-entries needs a value")?;
+                opts.max_entries = Some(v.parse().map_err(|_| format!("Invalid --max-entries: {v}"))?);
+            }
+            "--file" => opts.file = Some(args.next().ok_or("--file needs a value")?),
+            "--format" => {
+                let v = args.next().ok_or("--format needs a value")?;
+                opts.format = Format::parse(&v).ok_or(format!("Unknown format: {v}"))?;
+            }
+            "--wide" | "--pretty" => opts.layout = LayoutStyle::Wide,
+            "--sort" => {
+                let v = args.next().ok_or("--sort needs a value")?;
+                opts.sort = SortKey::parse(&v).ok_or(format!("Unknown sort key: {v}"))?;
+            }
+            "--export-ics" => opts.export_ics = Some(args.next().ok_or("--export-ics needs a file")?),
+            "--from" | "--to" => {
+                let v = args.next().ok_or(format!("{arg} needs an hour"))?;
+                let h: u32 = v.trim_end_matches(":00").parse().ok().filter(|h| *h < 24)
+                    .ok_or(format!("Invalid {arg}: {v}"))?;
+                let (from, to) = opts.hour_range.unwrap_or((0, 23));
+                opts.hour_range = Some(if arg == "--from" { (h, to) } else { (from, h) });
+            }
+            "--days" => {
+                let v = args.next().ok_or("--days needs a value")?;
+                opts.days = Some(v.parse().ok().filter(|d| *d > 0).ok_or(format!("Invalid --days: {v}"))?);
+            }
+            "--max-days-from-now" => {
+                let v = args.next().ok_or("--max-days-from-now needs a value")?;
+                opts.max_days_from_now =
+                    Some(v.parse().ok().filter(|d| *d > 0).ok_or(format!("Invalid --max-days-from-now: {v}"))?);
+            }
+            "--max-age" => {
+                let v = args.next().ok_or("--max-age needs a value")?;
+                opts.max_age = Some(v.parse().map_err(|_| format!("Invalid --max-age: {v}"))?);
+            }
+            "--units" => {
+                let v = args.next().ok_or("--units needs a value")?;
+                opts.units = Units::parse(&v).ok_or(format!("Unknown units: {v}"))?;
+            }
+            "--width-per-hour" => {
+                let v = args.next().ok_or("--width-per-hour needs a column width")?;
+                opts.width_per_hour = Some(v.parse().map_err(|_| format!("Invalid --width-per-hour: {v}"))?);
+            }
+            // Dev aids for previewing how a given weather renders, not listed in --help
+            "--condition-override" => {
+                let v = args.next().ok_or("--condition-override needs a condition")?.to_lowercase();
+                if !CONDITIONS.contains(&v.as_str()) {
+                    return Err(format!("Unknown condition: {v} (one of {})", CONDITIONS.join(", ")));
+                }
+                opts.condition_override = Some(v);
+            }
+            "--temp-override" | "--rain-override" => {
+                let v = args.next().ok_or(format!("{arg} needs a value"))?;
+                let n: f64 = v.parse().map_err(|_| format!("Invalid {arg}: {v}"))?;
+                if arg == "--temp-override" { opts.temp_override = Some(n) } else { opts.rain_override = Some(n) }
+            }
+            "--columns" => {
+                let v = args.next().ok_or("--columns needs a list")?;
+                for col in v.split(',').map(str::trim) {
+                    match col {
+                        "accum" => opts.accum = true,
+                        _ => return Err(format!("Unknown column: {col}")),
+                    }
+                }
+            }
+            "--output-json" => opts.output_json = Some(args.next().ok_or("--output-json needs a file")?),
+            "--emit-metrics" => opts.emit_metrics = Some(args.next().ok_or("--emit-metrics needs a file")?),
+            "--seed-cache" => opts.seed_cache = Some(args.next().ok_or("--seed-cache needs a file")?),
+            "--profile" => {
+                args.next();
+            }
+            "--limit-rows" => {
+                let v = args.next().ok_or("--limit-rows needs a value")?;
+                opts.limit_rows = Some(v.parse().map_err(|_| format!("Invalid --limit-rows: {v}"))?);
+            }
+            "--aggregate" => {
+                let v = args.next().ok_or("--aggregate needs mean or median")?;
+                opts.aggregate = Some(Aggregate::parse(&v).ok_or(format!("Unknown aggregate: {v}"))?);
+            }
+            "--wind-scale" => {
+                let v = args.next().ok_or("--wind-scale needs a value")?;
+                opts.wind_scale = WindScale::parse(&v).ok_or(format!("Unknown wind scale: {v}"))?;
+            }
+            s if s.starts_with("--") => return Err(format!("Unknown option: {s}")),
+            _ => words.push(arg),
+        }
+    }
+    opts.city = location(&words, &cfg);
+    opts.decimal_comma = decimal_sep.unwrap_or(opts.lang == Lang::De);
+    // The garden frost line is 0 °C, so it can only be converted once --units is known
+    if profile.as_deref() == Some("garden") && opts.min_temp.is_none() {
+        opts.min_temp = Some(to_unit(0.0, &opts));
+    }
+    Ok(opts)
+}
+
+fn location(words: &[String], cfg: &config::Config) -> String {
+    match (words.is_empty(), &cfg.city, cfg.default_coords) {
+        (false, ..) => words.join(" "),
+        (true, Some(city), _) => city.clone(),
+        (true, None, Some([lat, lon])) => format!("{lat},{lon}"),
+        (true, None, None) => String::new(),
+    }
+}
+
+const HELP: &str = "\
+Usage: weather [OPTIONS] [CITY | LAT,LON[,ALT][,LABEL]]
+
+Location precedence:
+  1. city or coordinates on the command line
+  2. city in $XDG_CONFIG_HOME/weather/config.toml (default ~/.config)
+  3. default_coords = [lat, lon] in the config file
+  4. interactive prompt (skipped with --no-prompt or without a terminal)
+
+  With --location-codes, full Plus Codes (8FVC9G8F+6X) are decoded locally and
+  what3words addresses (///filled.count.soap) are looked up with W3W_API_KEY
+
+Hour filter:
+  --from H --to H shows hours H through H; with --from 22 --to 6 the range
+  wraps past midnight, from 22:00 through 06:00 the next day
+
+Units:
+  --units metric|imperial, else `units` in the config file, else imperial
+  for en_US locales (LC_ALL, LC_MEASUREMENT, LANG) and metric elsewhere.
+  Bright Sky is always asked for metric (units=dwd): temperature, wind,
+  precipitation and visibility arrive as °C, km/h, mm and m from the server,
+  Fahrenheit, m/s and Beaufort are converted here
+
+Profiles (--profile NAME, other flags override them):
+  commute   morning and evening dayparts, rain shading and rain hours
+  garden    frost warnings below 0°, running precipitation total in mm
+  outdoor   golden hours, apparent temperature and the current hour; the
+            wind column shows whenever stations report wind, Bright Sky
+            has no UV index so none is shown
+
+Request window:
+  --days N fetches N days (default 3). Forecasts never reach further than
+  16 days ahead; --max-days-from-now N changes the cap, a longer --days is
+  clamped to it with a warning
+";
+
+// Presets only fill in defaults, flags given on the command line still win
+fn apply_profile(opts: &mut Options, name: &str) -> Result<(), String> {
+    match name {
+        "commute" => {
+            opts.interval_summary = true;
+            opts.dayparts = Some(&["morning", "evening"]);
+            opts.shade_rain = Some(50.0);
+            opts.rain_hours = true;
+        }
+        "garden" => {
+            opts.accum = true;
+        }
+        "outdoor" => {
+            opts.daylight = true;
+            opts.comfort = true;
+            opts.highlight_now = true;
+        }
+        _ => return Err(format!("Unknown profile: {name}")),
+    }
+    Ok(())
+}
+
+fn weekday(date: NaiveDate, lang: Lang) -> &'static str {
+    let i = date.weekday().num_days_from_monday() as usize;
+    match lang {
+        Lang::En => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"][i],
+        Lang::De => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"][i],
+    }
+}
+
+fn weekday_long(date: NaiveDate, lang: Lang) -> &'static str {
+    let i = date.weekday().num_days_from_monday() as usize;
+    match lang {
+        Lang::En => ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"][i],
+        Lang::De => ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"][i],
+    }
+}
+
+fn day_label(date: NaiveDate, today: NaiveDate, opts: &Options) -> String {
+    let lang = opts.lang;
+    match ((date - today).num_days(), lang) {
+        _ if opts.no_today_label => format!("{} {}", weekday(date, lang), date.format("%d.%m.")),
+        (0, Lang::En) => "Today".to_string(),
+        (0, Lang::De) => "Heute".to_string(),
+        (1, Lang::En) => "Tomorrow".to_string(),
+        (1, Lang::De) => "Morgen".to_string(),
+        _ if opts.layout == LayoutStyle::Wide => format!("{} {}", weekday_long(date, lang), date.format("%d.%m.")),
+        _ => format!("{} {}", weekday(date, lang), date.format("%d.%m.")),
+    }
+}
+
+// Widest label the current language produces for the coming week, so columns line up in every locale
+fn label_width(today: NaiveDate, opts: &Options) -> usize {
+    let widest = (0..9)
+        .map(|i| display_width(&day_label(today + chrono::Duration::days(i), today, opts)))
+        .max()
+        .unwrap_or(10);
+    if opts.layout == LayoutStyle::Wide { widest + 2 } else { widest }
+}
+
+fn divider(opts: &Options) -> String {
+    let n = if opts.layout == LayoutStyle::Wide { 60 } else { 38 };
+    "─".repeat(opts.max_width.map_or(n, |w| n.min(w.saturating_sub(2))))
+}
+
+fn dec(s: String, opts: &Options) -> String {
+    if opts.decimal_comma { s.replace('.', ",") } else { s }
 }
 
 fn tc(t: f64) -> &'static str {
@@ -39,14 +929,117 @@ fn tc(t: f64) -> &'static str {
     else { RED }
 }
 
+fn tc256(t: f64) -> String {
+    const GRADIENT: [u8; 20] = [
+        21, 27, 33, 39, 45, 51, 50, 49, 48, 47, 46, 82, 118, 154, 190, 226, 220, 214, 208, 196,
+    ];
+    let pos = ((t + 20.0) / 60.0 * (GRADIENT.len() - 1) as f64).round();
+    let i = pos.clamp(0.0, (GRADIENT.len() - 1) as f64) as usize;
+    format!("\x1b[38;5;{}m", GRADIENT[i])
+}
+
+fn temp_color(t: f64, opts: &Options) -> String {
+    if opts.color_256 { tc256(t) } else { tc(t).to_string() }
+}
+
 fn rc(p: f64) -> &'static str {
     if p >= 70.0 { RED }
     else if p >= 40.0 { YELLOW }
     else { DIM }
 }
 
+// Bands are picked in °C, then quoted in the display unit so the note matches the figures beside it
+fn explain_temp(t: f64, opts: &Options) -> String {
+    let unit = if opts.units == Units::Imperial { "°F" } else { "°C" };
+    let b = |c: f64| format!("{:.0}", to_unit(c, opts));
+    let band = if t < 0.0 { format!("BLUE below {} {unit}", b(0.0)) }
+    else if t < 10.0 { format!("CYAN band {}-{} {unit}", b(0.0), b(10.0)) }
+    else if t < 20.0 { format!("GREEN band {}-{} {unit}", b(10.0), b(20.0)) }
+    else if t < 30.0 { format!("YELLOW band {}-{} {unit}", b(20.0), b(30.0)) }
+    else { format!("RED {} {unit} and above", b(30.0)) };
+    format!("temp {:.1} {unit}→{band}", to_unit(t, opts))
+}
+
+fn explain_rain(p: f64) -> String {
+    let band = if p >= 70.0 { "RED 70 and above" }
+    else if p >= 40.0 { "YELLOW band 40-70" }
+    else { "DIM below 40" };
+    format!("rain {p:.0}%→{band}")
+}
+
+fn explain_icon(conds: &[String], cloud: Option<f64>, lang: Lang) -> String {
+    let mut present = ["thunderstorm", "rain", "snow", "sleet", "hail", "fog", "cloudy"]
+        .into_iter()
+        .filter(|c| conds.iter().any(|s| s == c))
+        .map(|c| condition_name(c, lang));
+    match present.next() {
+        None => match cloud {
+            Some(c) => format!("no notable condition, {c:.0}% cloud cover→{}", sky_icon("dry", Some(c))),
+            None => "no notable condition→sun".to_string(),
+        },
+        Some(top) => {
+            let rest: Vec<&str> = present.collect();
+            if rest.is_empty() { format!("{top} only") } else { format!("{top} beats {}", rest.join(", ")) }
+        }
+    }
+}
+
+fn beaufort(speed_ms: f64) -> (u8, &'static str) {
+    const SCALE: [(f64, &str); 12] = [
+        (0.5, "Calm"),
+        (1.6, "Light air"),
+        (3.4, "Light breeze"),
+        (5.5, "Gentle breeze"),
+        (8.0, "Moderate breeze"),
+        (10.8, "Fresh breeze"),
+        (13.9, "Strong breeze"),
+        (17.2, "Near gale"),
+        (20.8, "Gale"),
+        (24.5, "Strong gale"),
+        (28.5, "Storm"),
+        (32.7, "Violent storm"),
+    ];
+    for (n, (limit, desc)) in SCALE.iter().enumerate() {
+        if speed_ms < *limit {
+            return (n as u8, desc);
+        }
+    }
+    (12, "Hurricane force")
+}
+
+fn fmt_wind(kmh: f64, scale: WindScale, long: bool) -> String {
+    match scale {
+        WindScale::Kmh => format!("{kmh:3.0} km/h"),
+        WindScale::Ms => format!("{:4.1} m/s", kmh / 3.6),
+        WindScale::Beaufort => {
+            let (n, desc) = beaufort(kmh / 3.6);
+            // The short form stays as wide as "nn km/h" and "n.n m/s" so card cells line up under "Wind"
+            if long { format!("Bft {n:<2} {desc}") } else { format!("Bft {n:<4}") }
+        }
+    }
+}
+
+fn precip_class(mm: f64, lang: Lang) -> &'static str {
+    let i = if mm < 0.1 { return "" }
+    else if mm < 0.5 { 0 }
+    else if mm < 2.5 { 1 }
+    else if mm < 7.6 { 2 }
+    else { 3 };
+    match lang {
+        Lang::En => ["drizzle", "light", "moderate", "heavy"][i],
+        Lang::De => ["Niesel", "leicht", "mäßig", "stark"][i],
+    }
+}
+
+fn custom_icon(cond: &str) -> Option<&'static str> {
+    ICONS.get()?.get(cond).map(String::as_str)
+}
+
 fn icon(cond: &str) -> &'static str {
-    match cond {
+    if let Some(s) = custom_icon(cond) {
+        return s;
+    }
+    match cond.to_ascii_lowercase().as_str() {
         "thunderstorm" => "⛈️",
         "rain" => "🌧️",
         "snow" => "❄️",
@@ -58,86 +1051,1103 @@ fn icon(cond: &str) -> &'static str {
     }
 }
 
-fn pick_icon(conds: &[String]) -> &'static str {
+const CONDITIONS: [&str; 8] = ["dry", "cloudy", "fog", "rain", "sleet", "snow", "hail", "thunderstorm"];
+
+fn condition_description(cond: &str) -> &'static str {
+    match cond {
+        "dry" => "No precipitation expected; the sky may still be partly cloudy",
+        "cloudy" => "Mostly overcast sky without precipitation",
+        "fog" => "Water droplets near the ground reducing visibility below 1 km",
+        "rain" => "Liquid precipitation, from drizzle to heavy showers",
+        "sleet" => "A mix of rain and snow, or snow melting as it falls",
+        "snow" => "Frozen precipitation falling as ice crystal flakes",
+        "hail" => "Balls or lumps of ice from strong convective clouds",
+        "thunderstorm" => "Convective storm with lightning and thunder, often with heavy rain",
+        _ => "Unknown condition",
+    }
+}
+
+fn print_legend(lang: Lang) {
+    for cond in CONDITIONS {
+        out!("  {}  {BOLD}{:<12}{RESET} {DIM}{}{RESET}", icon(cond), condition_name(cond, lang), condition_description(cond));
+    }
+}
+
+fn condition_name(cond: &str, lang: Lang) -> &str {
+    match lang {
+        Lang::En => cond,
+        Lang::De => match cond {
+            "thunderstorm" => "Gewitter",
+            "rain" => "Regen",
+            "snow" => "Schnee",
+            "sleet" => "Schneeregen",
+            "hail" => "Hagel",
+            "fog" => "Nebel",
+            "cloudy" => "bewölkt",
+            "dry" => "trocken",
+            _ => cond,
+        },
+    }
+}
+
+fn dominant(conds: &[String]) -> &'static str {
     for c in ["thunderstorm", "rain", "snow", "sleet", "hail", "fog", "cloudy"] {
         if conds.iter().any(|s| s == c) {
-            return icon(c);
+            return c;
         }
     }
-    icon("dry")
+    "dry"
 }
 
-fn geocode(city: &str) -> Option<(f64, f64, String)> {
-    let url = format!(
-        "https://nominatim.openstreetmap.org/search?q={}&format=json&limit=1",
-        city.replace(' ', "+")
-    );
-    let mut resp = ureq::get(&url)
-        .header("User-Agent", "weather-cli")
-        .call()
-        .ok()?;
-    let body = resp.body_mut().read_to_string().ok()?;
-    let results: Vec<serde_json::Value> = serde_json::from_str(&body).ok()?;
-    let first = results.first()?;
-    let lat: f64 = first["lat"].as_str()?.parse().ok()?;
-    let lon: f64 = first["lon"].as_str()?.parse().ok()?;
-    let name = first["display_name"].as_str()?.split(',').next()?.trim().to_string();
-    Some((lat, lon, name))
+fn sky_icon(cond: &str, cloud: Option<f64>) -> &'static str {
+    match (cond, cloud) {
+        _ if custom_icon(cond).is_some() => icon(cond),
+        ("dry", Some(c)) if c >= 85.0 => "☁️",
+        ("dry", Some(c)) if c >= 60.0 => "⛅",
+        ("dry", Some(c)) if c >= 25.0 => "🌤️",
+        _ => icon(cond),
+    }
 }
 
-fn main() {
-    let city = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
-    let city = if city.is_empty() {
-        eprint!("City: ");
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input).unwrap();
-        input.trim().to_string()
-    } else {
-        city
-    };
+fn is_night(h: &Hour, sun: Option<(NaiveDateTime, NaiveDateTime)>) -> bool {
+    match (sun, h.utc) {
+        (Some((rise, set)), Some(at)) => at < rise || at > set,
+        _ => h.time[..2].parse::<u32>().is_ok_and(|hour| !(6..21).contains(&hour)),
+    }
+}
 
-    let (lat, lon, name) = match geocode(&city) {
-        Some(v) => v,
-        None => {
-            eprintln!("Could not find city: {city}");
-            return;
-        }
-    };
+fn hour_icon(h: &Hour, sun: Option<(NaiveDateTime, NaiveDateTime)>) -> &'static str {
+    match h.cloud {
+        _ if h.cond != "dry" || custom_icon("dry").is_some() || !is_night(h, sun) => sky_icon(&h.cond, h.cloud),
+        Some(c) if c >= 85.0 => "☁️",
+        _ => "🌙",
+    }
+}
 
-    let now = Local::now();
-    let today = now.format("%Y-%m-%d").to_string();
-    let date_from = now.format("%Y-%m-%dT%H:00").to_string();
-    let date_to = (now + chrono::Duration::days(3)).format("%Y-%m-%dT%H:00").to_string();
+fn explain_hour_icon(h: &Hour, sun: Option<(NaiveDateTime, NaiveDateTime)>, lang: Lang) -> String {
+    let name = condition_name(&h.cond, lang);
+    if h.cond != "dry" || custom_icon("dry").is_some() {
+        return name.to_string();
+    }
+    let night = if is_night(h, sun) { " at night" } else { "" };
+    match (hour_icon(h, sun), h.cloud) {
+        ("🌙", _) => format!("{name} at night→moon"),
+        ("☀️", _) => format!("{name}→sun"),
+        (ic, Some(c)) => format!("{name}{night}, {c:.0}% cloud cover→{ic}"),
+        (ic, None) => format!("{name}→{ic}"),
+    }
+}
 
-    let url = format!(
-        "https://api.brightsky.dev/weather?lat={lat}&lon={lon}&date={}&last_date={}",
-        date_from, date_to
-    );
+fn pick_icon(conds: &[String], cloud: Option<f64>) -> &'static str {
+    sky_icon(dominant(conds), cloud)
+}
 
-    let body: String = match ureq::get(&url).call() {
-        Ok(mut resp) => resp.body_mut().read_to_string().unwrap_or_default(),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            return;
+fn cond_color(cond: &str) -> &'static str {
+    match cond {
+        "thunderstorm" => MAGENTA,
+        "rain" => BLUE,
+        "snow" | "sleet" | "hail" => CYAN,
+        "fog" | "cloudy" => GRAY,
+        _ => BRIGHT_YELLOW,
+    }
+}
+
+fn coord(v: &serde_json::Value) -> Option<f64> {
+    v.as_str().and_then(|s| s.trim().parse().ok()).or_else(|| v.as_f64())
+}
+
+fn place_from(result: &serde_json::Value) -> Option<(f64, f64, String)> {
+    let lat = coord(&result["lat"])?;
+    let lon = coord(&result["lon"])?;
+    let name = result["display_name"].as_str()?.split(',').next()?.trim().to_string();
+    Some((lat, lon, name))
+}
+
+fn postal_code(input: &str) -> Option<(Option<&str>, &str)> {
+    let is_code = |s: &str| (3..=10).contains(&s.len()) && s.chars().all(|c| c.is_ascii_digit() || c == '-');
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    match parts[..] {
+        [code] if is_code(code) => Some((None, code)),
+        [country, code] if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) && is_code(code) => {
+            Some((Some(country), code))
+        }
+        _ => None,
+    }
+}
+
+const QUERY: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+fn encode_query(city: &str) -> String {
+    let city = city.trim_matches(|c: char| c == '?' || c.is_whitespace());
+    utf8_percent_encode(city, QUERY).to_string()
+}
+
+fn geocode(client: &dyn HttpClient, city: &str, verbose: bool) -> Result<Option<(f64, f64, String)>, String> {
+    if let Some((country, code)) = postal_code(city) {
+        let mut url = format!("https://nominatim.openstreetmap.org/search?postalcode={code}&format=json&limit=1");
+        if let Some(country) = country {
+            url += &format!("&country={country}");
+        }
+        if let Some(place) = search(client, &url)? {
+            return Ok(Some(place));
+        }
+    }
+    let mut query = city;
+    loop {
+        let url = format!("https://nominatim.openstreetmap.org/search?q={}&format=json&limit=1", encode_query(query));
+        if let Some(place) = search(client, &url)? {
+            return Ok(Some(place));
+        }
+        let Some((rest, _)) = query.rsplit_once(',') else { return Ok(None) };
+        if verbose {
+            eprintln!("No match for \"{query}\", retrying with \"{}\"", rest.trim());
+        }
+        query = rest.trim();
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = (prev + usize::from(ca != *cb)).min(row[j] + 1).min(cur + 1);
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+fn suggest(client: &dyn HttpClient, city: &str) -> Option<String> {
+    let city = city.trim().to_lowercase();
+    let prefix: String = city.chars().take((city.chars().count() / 2).max(3)).collect();
+    let url = format!("https://nominatim.openstreetmap.org/search?q={}&format=json&limit=5", encode_query(&prefix));
+    let body = nominatim_get(client, &url).ok()?;
+    let results: Vec<serde_json::Value> = serde_json::from_str(&body).ok()?;
+    let max = (city.chars().count() / 3).max(2);
+    results
+        .iter()
+        .filter_map(|r| place_from(r).map(|(_, _, name)| name))
+        .map(|name| (levenshtein(&city, &name.to_lowercase()), name))
+        .filter(|(d, _)| *d <= max)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, name)| name)
+}
+
+#[derive(Deserialize)]
+struct IpLocation {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    city: Option<String>,
+    reason: Option<String>,
+}
+
+fn locate_by_ip(client: &dyn HttpClient) -> Result<(f64, f64, String), String> {
+    let body = client.get("https://ipapi.co/json/").map_err(|e| e.to_string())?;
+    let loc: IpLocation = serde_json::from_str(&body).map_err(|e| format!("Geolocation JSON error: {e}"))?;
+    match (loc.latitude, loc.longitude) {
+        (Some(lat), Some(lon)) => Ok((lat, lon, loc.city.unwrap_or_else(|| format!("{lat:.2}, {lon:.2}")))),
+        _ => Err(format!("Could not locate you by IP: {}", loc.reason.as_deref().unwrap_or("no coordinates returned"))),
+    }
+}
+
+// Nominatim allows one request per second, whichever lookup (postal, retries, suggestions) makes it
+#[cfg(not(test))]
+const NOMINATIM_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+#[cfg(test)]
+const NOMINATIM_INTERVAL: std::time::Duration = std::time::Duration::ZERO;
+
+static LAST_NOMINATIM: Mutex<Option<std::time::Instant>> = Mutex::new(None);
+
+fn nominatim_get(client: &dyn HttpClient, url: &str) -> Result<String, FetchError> {
+    if let Ok(mut last) = LAST_NOMINATIM.lock() {
+        if let Some(wait) = last.map(|t| NOMINATIM_INTERVAL.saturating_sub(t.elapsed())) {
+            std::thread::sleep(wait);
         }
+        *last = Some(std::time::Instant::now());
+    }
+    client.get(url)
+}
+
+fn search(client: &dyn HttpClient, url: &str) -> Result<Option<(f64, f64, String)>, String> {
+    let body = nominatim_get(client, url).map_err(|e| e.to_string())?;
+    let json: serde_json::Value = serde_json::from_str(&body).map_err(|e| format!("Geocoding JSON error: {e}"))?;
+    let Some(results) = json.as_array() else {
+        let error = &json["error"];
+        return Err(match error.as_str().or(error["message"].as_str()) {
+            Some(msg) => format!("Geocoding service error: {msg}"),
+            None => "Geocoding service returned an unexpected response".to_string(),
+        });
     };
+    Ok(results.first().and_then(place_from))
+}
 
-    let resp: Response = match serde_json::from_str(&body) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("JSON error: {e}");
-            return;
+const WIDE_MAX_DIST_KM: u32 = 200;
+const DEFAULT_STATION_DISTANCE_KM: f64 = 25.0;
+
+fn has_weather(body: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(body)
+        .is_ok_and(|v| v["weather"].as_array().is_some_and(|a| !a.is_empty()))
+}
+
+fn fetch_forecast(client: &dyn HttpClient, url: &str) -> Result<(String, bool), FetchError> {
+    let first = match client.get(url) {
+        Ok(b) if has_weather(&b) => return Ok((b, false)),
+        Err(e) if e.status != Some(404) => return Err(e),
+        other => other,
+    };
+    let wide = format!("{url}&max_dist={}", WIDE_MAX_DIST_KM * 1000);
+    match client.get(&wide) {
+        Ok(b) if has_weather(&b) => Ok((b, true)),
+        _ => first.map(|b| (b, false)),
+    }
+}
+
+fn fetch_alerts(client: &dyn HttpClient, lat: f64, lon: f64) -> Result<Vec<Alert>, String> {
+    let url = format!("https://api.brightsky.dev/alerts?lat={lat}&lon={lon}");
+    let body = client.get(&url).map_err(|e| e.to_string())?;
+    let resp: AlertsResponse = serde_json::from_str(&body).map_err(|e| format!("JSON error: {e}"))?;
+    Ok(resp.alerts)
+}
+
+fn severity_color(severity: &str) -> &'static str {
+    match severity {
+        "extreme" | "severe" => RED,
+        "moderate" => YELLOW,
+        _ => CYAN,
+    }
+}
+
+fn alert_time(ts: &str, opts: &Options) -> Option<String> {
+    let dt = localize(ts, opts.timezone)?;
+    Some(format!("{} {}", weekday(dt.date(), opts.lang), dt.format("%H:%M")))
+}
+
+fn print_alerts(alerts: &[Alert], opts: &Options) {
+    for a in alerts {
+        let headline = match opts.lang {
+            Lang::De => a.headline_de.as_ref().or(a.headline_en.as_ref()),
+            Lang::En => a.headline_en.as_ref().or(a.headline_de.as_ref()),
+        };
+        let color = severity_color(&a.severity);
+        let bold = if a.severity == "extreme" { BOLD } else { "" };
+        let from = a.onset.as_deref().and_then(|t| alert_time(t, opts)).unwrap_or_default();
+        let until = a.expires.as_deref().and_then(|t| alert_time(t, opts)).unwrap_or_default();
+        out!(
+            "  {bold}{color}⚠ {}{RESET} {DIM}({}, {from} – {until}){RESET}",
+            headline.map_or("Weather warning", |h| h.as_str()),
+            a.severity
+        );
+    }
+    if !alerts.is_empty() {
+        out!();
+    }
+}
+
+fn print_current(cur: &CurrentWeather, opts: &Options) {
+    let time = localize(&cur.timestamp, opts.timezone).map(|t| t.format("%H:%M").to_string()).unwrap_or_default();
+    let cond = cur.condition.as_deref().unwrap_or("dry");
+    let ic = sky_icon(cond, cur.cloud_cover);
+    let temp = match cur.temperature {
+        Some(t) => format!("{BOLD}{}{}°{RESET}", temp_color(t, opts), dec(format!("{:.1}", snap(t, opts)), opts)),
+        None => "–".to_string(),
+    };
+    let name = condition_name(cond, opts.lang);
+    let mut line = format!("  {BOLD}Now{RESET} {DIM}{time}{RESET}  {ic}  {temp}  {name}");
+    if let Some(mm) = cur.precipitation_60 {
+        line += &format!("  {DIM}rain{RESET} {} mm", dec(format!("{mm:.1}"), opts));
+    }
+    if let Some(w) = cur.wind_speed_10 {
+        line += &format!("  {DIM}wind{RESET} {}", fmt_wind(w, opts.wind_scale, true).trim_start());
+    }
+    out!("{line}");
+}
+
+fn json_output(r: &Report, opts: &Options) -> serde_json::Value {
+    let scale = if opts.prob_as_fraction { 0.01 } else { 1.0 };
+    let days: Vec<serde_json::Value> = r
+        .days
+        .iter()
+        .map(|(day, d)| {
+            let date = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok();
+            let mut hours = serde_json::to_value(&d.hours).unwrap_or_default();
+            for h in hours.as_array_mut().into_iter().flatten() {
+                h["rp"] = serde_json::json!(h["rp"].as_f64().unwrap_or(0.0) * scale);
+            }
+            serde_json::json!({
+                "date": day,
+                "iso_week": date.map(|dt| dt.iso_week().week()),
+                "day_of_year": date.map(|dt| dt.ordinal()),
+                "hi": d.hi,
+                "lo": d.lo,
+                "max_rain_probability": d.max_rp * scale,
+                "max_wind": d.max_wind,
+                "conditions": d.conds,
+                "hours": hours,
+            })
+        })
+        .collect();
+    // Always metric regardless of --units, say so for consumers
+    let units = serde_json::json!({
+        "temperature": "°C",
+        "wind_speed": "km/h",
+        "precipitation": "mm",
+        "visibility": "m",
+        "rain_probability": if opts.prob_as_fraction { "fraction" } else { "%" },
+    });
+    serde_json::json!({
+        "units": units,
+        "location": { "name": r.name, "lat": r.lat, "lon": r.lon },
+        "stations": r.stations,
+        "days": days,
+    })
+}
+
+fn temp_head(opts: &Options) -> &'static str {
+    match opts.units {
+        Units::Metric => "Temp (°C)",
+        Units::Imperial => "Temp (°F)",
+    }
+}
+
+fn rain_head(opts: &Options) -> &'static str {
+    if opts.prob_as_fraction { "Rain (p)" } else { "Rain (%)" }
+}
+
+fn fmt_prob(rp: f64, opts: &Options) -> String {
+    if opts.prob_as_fraction { dec(format!("{:.2}", rp / 100.0), opts) } else { format!("{rp:3.0}%") }
+}
+
+// Running-text variant of fmt_prob, `unit` is how the sentence spells percent
+fn fmt_chance(rp: f64, unit: &str, opts: &Options) -> String {
+    if opts.prob_as_fraction { dec(format!("{:.2}", rp / 100.0), opts) } else { format!("{rp:.0}{unit}") }
+}
+
+fn sorted_counts(counts: &HashMap<String, u32>) -> Vec<(&String, &u32)> {
+    let mut counts: Vec<(&String, &u32)> = counts.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    counts
+}
+
+fn breakdown(d: &DaySummary, lang: Lang) -> String {
+    sorted_counts(&d.cond_hours)
+        .iter()
+        .map(|(cond, n)| format!("{} {n}h", condition_name(cond, lang)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn condition_stats(days: &[(String, DaySummary)], lang: Lang) -> String {
+    let mut total: HashMap<String, u32> = HashMap::new();
+    for (_, d) in days {
+        for (cond, n) in &d.cond_hours {
+            *total.entry(cond.clone()).or_insert(0) += n;
+        }
+    }
+    sorted_counts(&total)
+        .iter()
+        .map(|(cond, n)| format!("{} {} {n}h", icon(cond), condition_name(cond, lang)))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+// Apparent temperature (Steadman, as used by the BoM), dropping terms whose input is missing
+fn comfort(temp: f64, humidity: Option<f64>, wind: Option<f64>) -> (f64, &'static str) {
+    let vapour = humidity.map_or(0.0, |rh| 0.33 * rh / 100.0 * 6.105 * (17.27 * temp / (237.7 + temp)).exp() - 4.0);
+    let at = temp + vapour - wind.map_or(0.0, |kmh| 0.7 * kmh / 3.6);
+    let label = if at < 0.0 { "freezing" }
+    else if at < 10.0 { "cold" }
+    else if at < 18.0 { "cool" }
+    else if at < 25.0 { "comfortable" }
+    else if at < 32.0 { "warm" }
+    else { "hot" };
+    (at, label)
+}
+
+// Rough monthly mean temperatures for the northern hemisphere by latitude band
+fn seasonal_normal(month0: usize, lat: f64) -> f64 {
+    let month = if lat < 0.0 { (month0 + 6) % 12 } else { month0 };
+    let table: [f64; 12] = match lat.abs() {
+        l if l < 23.5 => [26.0; 12],
+        l if l < 40.0 => [8.0, 9.0, 12.0, 15.0, 19.0, 23.0, 26.0, 26.0, 22.0, 17.0, 12.0, 9.0],
+        l if l < 55.0 => [0.0, 1.0, 5.0, 9.0, 14.0, 17.0, 19.0, 19.0, 15.0, 10.0, 5.0, 1.0],
+        l if l < 66.5 => [-5.0, -5.0, -2.0, 4.0, 10.0, 15.0, 17.0, 16.0, 11.0, 6.0, 1.0, -3.0],
+        _ => [-20.0, -20.0, -17.0, -10.0, -2.0, 4.0, 7.0, 5.0, 0.0, -7.0, -14.0, -18.0],
+    };
+    table[month]
+}
+
+fn to_unit(t: f64, opts: &Options) -> f64 {
+    match opts.units {
+        Units::Metric => t,
+        Units::Imperial => t * 9.0 / 5.0 + 32.0,
+    }
+}
+
+fn delta_unit(dt: f64, opts: &Options) -> f64 {
+    match opts.units {
+        Units::Metric => dt,
+        Units::Imperial => dt * 9.0 / 5.0,
+    }
+}
+
+fn snap(t: f64, opts: &Options) -> f64 {
+    let t = to_unit(t, opts);
+    opts.round_temp_to.map_or(t, |step| (t / step).round() * step)
+}
+
+fn temp_width(temps: impl Iterator<Item = f64>) -> usize {
+    temps.map(|t| format!("{t:.1}").len()).fold(5, usize::max)
+}
+
+fn print_cards(days: &[(String, DaySummary)], today: NaiveDate, opts: &Options) {
+    let wide = opts.layout == LayoutStyle::Wide;
+    let width = label_width(today, opts);
+    let show_wind = days.iter().any(|(_, d)| d.max_wind.is_some());
+    let wind_head = if show_wind { "      Wind" } else { "" };
+    let pad = " ".repeat(width + 7);
+    let tw = temp_width(days.iter().flat_map(|(_, d)| [snap(d.lo, opts), snap(d.hi, opts)]));
+    let extra = " ".repeat(tw - 5);
+    out!("  {DIM}{pad}{extra}{:<13}{extra}{:>8}{wind_head}{RESET}", temp_head(opts), rain_head(opts));
+    out!("  {DIM}{}{RESET}", divider(opts));
+    let rain_max = days.iter().map(|(_, d)| d.max_rp).fold(0.0, f64::max);
+    let mut week = None;
+    for (day, d) in days {
+        if wide {
+            out!();
+        }
+        let ic = pick_icon(&d.conds, mean(&d.clouds));
+        let dt = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok();
+        if dt.is_none() {
+            eprintln!("Warning: unexpected day key {day:?}, showing it as is");
+        }
+        if let Some(dt) = dt
+            && opts.group_by_week
+            && week != Some(dt.iso_week())
+        {
+            week = Some(dt.iso_week());
+            match opts.lang {
+                Lang::En => out!("  {DIM}Week {}{RESET}", dt.iso_week().week()),
+                Lang::De => out!("  {DIM}KW {}{RESET}", dt.iso_week().week()),
+            }
+        }
+        let text = dt.map_or_else(|| day.clone(), |dt| day_label(dt, today, opts));
+        let label = if dt == Some(today) {
+            format!("{BOLD}{text:<width$}{RESET}")
+        } else {
+            format!("{text:<width$}")
+        };
+        let wind = match d.max_wind {
+            Some(w) => format!("  {}", fmt_wind(w, opts.wind_scale, false)),
+            None => String::new(),
+        };
+        let why = if opts.explain {
+            format!(
+                "  {DIM}# {}; lo {}; hi {}; {}{RESET}",
+                explain_icon(&d.conds, mean(&d.clouds), opts.lang), explain_temp(d.lo, opts), explain_temp(d.hi, opts), explain_rain(d.max_rp)
+            )
+        } else {
+            String::new()
+        };
+        let badge = if opts.comfort {
+            let (at, label) = comfort(d.hi, mean(&d.humidity), d.max_wind);
+            format!("  {}[{label} {}°]{RESET}", temp_color(at, opts), dec(format!("{:.0}", snap(at, opts)), opts))
+        } else {
+            String::new()
+        };
+        let bar = if opts.relative_rain { format!("  {BLUE}{}{RESET}", rain_bar(d.max_rp, rain_max)) } else { String::new() };
+        let anomaly = match d.anomaly {
+            Some(a) => {
+                let c = if a >= 1.0 { RED } else if a <= -1.0 { BLUE } else { DIM };
+                let text = if opts.lang == Lang::De { "zum Mittel" } else { "vs normal" };
+                format!("  {c}{}° {text}{RESET}", dec(format!("{:+.0}", delta_unit(a, opts)), opts))
+            }
+            None => String::new(),
+        };
+        let typical = match opts.aggregate {
+            Some(Aggregate::Mean) => mean(&d.temps).map(|t| ("mean", t)),
+            Some(Aggregate::Median) => Some(("median", median(&mut d.temps.clone()))),
+            None => None,
+        };
+        let typical = match typical {
+            Some((what, t)) if !t.is_nan() => {
+                format!("  {DIM}{what}{RESET} {}{}°{RESET}", temp_color(t, opts), dec(format!("{:.1}", snap(t, opts)), opts))
+            }
+            _ => String::new(),
+        };
+        let wet = if opts.rain_hours { format!("  {BLUE}☔ {:>2}h{RESET}", d.rain_hours) } else { String::new() };
+        let (lo_c, hi_c) = match opts.color_by {
+            ColorBy::Temp => (temp_color(d.lo, opts), temp_color(d.hi, opts)),
+            ColorBy::Condition => {
+                let c = cond_color(dominant(&d.conds)).to_string();
+                (c.clone(), c)
+            }
+        };
+        out!(
+            "  {label} {ic}  {lo_c}{}°{RESET}  …  {hi_c}{}°{RESET}  {}{}{RESET}{wind}{typical}{wet}{bar}{badge}{anomaly}{why}",
+            dec(format!("{:tw$.1}", snap(d.lo, opts)), opts), dec(format!("{:tw$.1}", snap(d.hi, opts)), opts), rc(d.max_rp), fmt_prob(d.max_rp, opts)
+        );
+        if opts.breakdown {
+            out!("  {}    {DIM}{}{RESET}", " ".repeat(width), breakdown(d, opts.lang));
         }
+        if let Some(note) = &d.dst_note {
+            out!("  {}    {DIM}{note}{RESET}", " ".repeat(width));
+        }
+    }
+}
+
+fn visibility_color(m: f64) -> &'static str {
+    if m < 200.0 { RED }
+    else if m < 1000.0 { YELLOW }
+    else { DIM }
+}
+
+fn fmt_visibility(m: f64) -> String {
+    if m < 1000.0 { format!("{m:.0}m") } else { format!("{:.1}km", m / 1000.0) }
+}
+
+fn rain_bar(value: f64, max: f64) -> String {
+    const WIDTH: usize = 8;
+    const PARTS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+    if max <= 0.0 {
+        return " ".repeat(WIDTH);
+    }
+    let eighths = (value / max * (WIDTH * 8) as f64).round() as usize;
+    let bar = format!("{}{}", "█".repeat(eighths / 8), PARTS[eighths % 8]);
+    format!("{bar:<WIDTH$}")
+}
+
+// Sunrise equation, returning UTC sunrise and sunset; None during polar day or night
+fn sun_times(date: NaiveDate, lat: f64, lon: f64) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let midnight = date.and_hms_opt(0, 0, 0)?.and_utc().timestamp() as f64;
+    let jd = midnight / 86400.0 + 2440587.5;
+    let n = (jd - 2451545.0 + 0.0008).ceil();
+    let j_star = n - lon / 360.0;
+    let m = (357.5291 + 0.98560028 * j_star).rem_euclid(360.0).to_radians();
+    let c = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let lambda = (m.to_degrees() + c + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+    let transit = 2451545.0 + j_star + 0.0053 * m.sin() - 0.0069 * (2.0 * lambda).sin();
+    let decl = (lambda.sin() * 23.4397f64.to_radians().sin()).asin();
+    let phi = lat.to_radians();
+    let cos_w = ((-0.833f64).to_radians().sin() - phi.sin() * decl.sin()) / (phi.cos() * decl.cos());
+    if !(-1.0..=1.0).contains(&cos_w) {
+        return None;
+    }
+    let w = cos_w.acos().to_degrees() / 360.0;
+    let at = |j: f64| DateTime::from_timestamp(((j - 2440587.5) * 86400.0) as i64, 0).map(|t| t.naive_utc());
+    Some((at(transit - w)?, at(transit + w)?))
+}
+
+fn rain_band(rp: f64) -> u8 {
+    if rp >= 70.0 { 2 } else if rp >= 40.0 { 1 } else { 0 }
+}
+
+fn collapse_runs(hours: &[Hour], sun: Option<(NaiveDateTime, NaiveDateTime)>) -> Vec<(usize, usize)> {
+    let key = |h: &Hour| (h.temp.round() as i64, rain_band(h.rp), hour_icon(h, sun));
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (i, h) in hours.iter().enumerate() {
+        match runs.last_mut() {
+            Some(run) if key(&hours[run.0]) == key(h) => run.1 = i,
+            _ => runs.push((i, i)),
+        }
+    }
+    runs
+}
+
+fn print_hourly(d: &DaySummary, opts: &Options) {
+    let gap = if opts.layout == LayoutStyle::Wide { "    " } else { "  " };
+    let show_precip = d.hours.iter().any(|h| h.precip.is_some());
+    let wind_w = d.hours.iter().filter_map(|h| h.wind).map(|w| fmt_wind(w, opts.wind_scale, true).len()).max();
+    let show_wind = wind_w.is_some();
+    let show_vis = d.hours.iter().any(|h| h.visibility.is_some());
+    let show_storm = d.hours.iter().any(|h| h.storm.is_some());
+    let mut runs = if opts.collapse { collapse_runs(&d.hours, d.sun) } else { (0..d.hours.len()).map(|i| (i, i)).collect() };
+    if let Some(n) = opts.sample_interval {
+        runs = runs.into_iter().step_by(n).collect();
+    }
+    let pad = if opts.collapse { " ".repeat(6) } else { String::new() };
+    let w = 5 + pad.len();
+    let tw = temp_width(d.hours.iter().map(|h| snap(h.temp, opts)));
+    let (time_h, int_h, vis_h, storm_h, total_h) = match opts.lang {
+        Lang::En => ("Time", "Intensity", "Visibility", "Storm", "Total"),
+        Lang::De => ("Zeit", "Stärke", "Sicht", "Blitz", "Summe"),
+    };
+    let mut head = format!("{time_h}{pad}{gap}{gap}{:>tw4$}{gap}{:>5}", temp_head(opts), rain_head(opts), tw4 = tw + 4);
+    if opts.delta { head += &format!("{gap}    Δ"); }
+    if show_precip { head += &format!("{gap}{int_h:<9}"); }
+    if show_vis { head += &format!("{gap}{vis_h:<10}"); }
+    if show_storm { head += &format!("{gap}{storm_h:<5}"); }
+    if show_wind { head += &format!("{gap} Wind"); }
+    if opts.accum { head += &format!("{gap}{total_h:>8}"); }
+    out!();
+    out!("  {DIM}{head}{RESET}");
+    out!("  {DIM}{}{RESET}", divider(opts));
+    if let Some(note) = &d.dst_note {
+        out!("  {DIM}{note}{RESET}");
+    }
+    let total = runs.len();
+    if let Some(n) = row_limit(opts) {
+        runs.truncate(n.max(1));
+    }
+    let hidden = total - runs.len();
+    let accum: Vec<f64> = d.hours.iter()
+        .scan(0.0, |sum, h| {
+            *sum += h.precip.unwrap_or(0.0);
+            Some(*sum)
+        })
+        .collect();
+    let rain_max = d.hours.iter().map(|h| h.rp).fold(0.0, f64::max);
+    let now = Utc::now().naive_utc();
+    let current = opts.highlight_now.then(|| {
+        runs.iter()
+            .filter_map(|&(i, _)| Some((i, (d.hours[i].utc? - now).num_minutes().abs())))
+            .filter(|&(_, gap)| gap <= 60)
+            .min_by_key(|&(_, gap)| gap)
+            .map(|(i, _)| i)
+    }).flatten();
+    let mut prev = None;
+    for (i, end) in runs {
+        let h = &d.hours[i];
+        let time = if end > i { format!("{}–{}", h.time, d.hours[end].time) } else { h.time.clone() };
+        let ic = hour_icon(h, d.sun);
+        let flagged = crosses(h.temp, opts).is_some();
+        let temp_c = match opts.color_by {
+            _ if flagged => format!("{BOLD}{MAGENTA}"),
+            ColorBy::Temp => temp_color(h.temp, opts),
+            ColorBy::Condition => cond_color(&h.cond).to_string(),
+        };
+        let mut row = format!(
+            "  {time:<w$}{gap}{ic}{gap}{temp_c}{}°{RESET}{gap}{}{:>7}{RESET}",
+            dec(format!("{:tw$.1}", snap(h.temp, opts)), opts), rc(h.rp), fmt_prob(h.rp, opts)
+        );
+        if opts.delta {
+            row += &match prev.map(|p: usize| delta_unit(h.temp - d.hours[p].temp, opts)) {
+                Some(dt) if dt > 0.0 => format!("{gap}{GREEN}{}°{RESET}", dec(format!("{dt:+5.1}"), opts)),
+                Some(dt) if dt < 0.0 => format!("{gap}{BLUE}{}°{RESET}", dec(format!("{dt:+5.1}"), opts)),
+                Some(dt) => format!("{gap}{DIM}{}°{RESET}", dec(format!("{dt:+5.1}"), opts)),
+                None => format!("{gap}{DIM}    —{RESET} "),
+            };
+        }
+        if show_precip {
+            row += &format!("{gap}{:<9}", precip_class(h.precip.unwrap_or(0.0), opts.lang));
+        }
+        if show_vis {
+            row += &match h.visibility {
+                Some(m) if h.cond == "fog" => format!("{gap}{}👁 {:<7}{RESET}", visibility_color(m), fmt_visibility(m)),
+                _ => format!("{gap}{:10}", ""),
+            };
+        }
+        if show_storm {
+            row += &match h.storm {
+                Some(p) => format!("{gap}{}⚡{p:3.0}%{RESET}", if p >= 30.0 { MAGENTA } else { DIM }),
+                None => format!("{gap}{:6}", ""),
+            };
+        }
+        if let Some(ww) = wind_w {
+            row += &format!("{gap}{:<ww$}", h.wind.map(|w| fmt_wind(w, opts.wind_scale, true)).unwrap_or_default());
+        }
+        if opts.accum {
+            row += &format!("{gap}{BLUE}{} mm{RESET}", dec(format!("{:5.1}", accum[end]), opts));
+        }
+        let why = if opts.explain {
+            let cond = explain_hour_icon(h, d.sun, opts.lang);
+            format!("  {DIM}# {cond}; {}; {}{RESET}", explain_temp(h.temp, opts), explain_rain(h.rp))
+        } else {
+            String::new()
+        };
+        if opts.relative_rain {
+            row += &format!("{gap}{BLUE}{}{RESET}", rain_bar(h.rp, rain_max));
+        }
+        if flagged {
+            row += &format!("{gap}{MAGENTA}⚠️{RESET}");
+        }
+        if opts.daylight
+            && let (Some((rise, set)), Some(at)) = (d.sun, h.utc)
+        {
+            let golden = chrono::Duration::hours(1);
+            if at < rise || at > set {
+                row = format!("{DIM}{}{RESET}", row.replace(RESET, &format!("{RESET}{DIM}")));
+            } else if at < rise + golden || at > set - golden {
+                row += &format!("{gap}{BRIGHT_YELLOW}✨{RESET}");
+            }
+        }
+        if opts.shade_rain.is_some_and(|t| h.rp >= t) {
+            row = format!("{RAIN_BG}{}{RESET}", row.replace(RESET, &format!("{RESET}{RAIN_BG}")));
+        }
+        if current == Some(i) {
+            row = format!("{BOLD}{}{RESET}", row.replacen("  ", "▶ ", 1).replace(RESET, &format!("{RESET}{BOLD}")));
+        }
+        out!("{row}{why}");
+        prev = Some(i);
+    }
+    if hidden > 0 {
+        out!("  {DIM}… +{hidden} more (use --pager){RESET}");
+    }
+}
+
+// Rows left on screen below what was already printed, keeping one for the footer and one for the prompt
+fn row_limit(opts: &Options) -> Option<usize> {
+    match opts.limit_rows {
+        Some(0) => None,
+        Some(n) => Some(n),
+        None if SINK.with_borrow(Option::is_none) && std::io::stdout().is_terminal() => {
+            let (_, rows) = crossterm::terminal::size().ok()?;
+            Some((rows as usize).saturating_sub(PRINTED.load(Ordering::Relaxed) + 2))
+        }
+        None => None,
+    }
+}
+
+fn print_strip(d: &DaySummary, col: usize, opts: &Options) {
+    let col = col.max(5);
+    let width = match MAX_WIDTH.load(Ordering::Relaxed) {
+        usize::MAX => crossterm::terminal::size().map_or(80, |(w, _)| w as usize),
+        w => w,
     };
+    let per_row = (width.saturating_sub(2) / col).max(1);
+    let cell = |color: &str, text: &str| format!("{color}{text}{RESET}{}", " ".repeat(col.saturating_sub(display_width(text))));
+    for chunk in d.hours.chunks(per_row) {
+        let mut rows = [String::new(), String::new(), String::new(), String::new()];
+        for h in chunk {
+            rows[0] += &cell(DIM, &h.time[..2]);
+            rows[1] += &cell("", hour_icon(h, d.sun));
+            rows[2] += &cell(&temp_color(h.temp, opts), &dec(format!("{:.0}°", snap(h.temp, opts)), opts));
+            rows[3] += &cell(rc(h.rp), fmt_prob(h.rp, opts).trim_start());
+        }
+        out!();
+        for row in rows {
+            out!("  {}", row.trim_end());
+        }
+    }
+}
+
+fn daypart(hour: u32) -> &'static str {
+    match hour {
+        6..12 => "morning",
+        12..18 => "afternoon",
+        18..24 => "evening",
+        _ => "night",
+    }
+}
+
+fn print_dayparts(d: &DaySummary, opts: &Options) {
+    out!();
+    out!("  {DIM}{:<10}{:>11} {}{RESET}", "", temp_head(opts), rain_head(opts));
+    out!("  {DIM}{}{RESET}", divider(opts));
+    for part in opts.dayparts.unwrap_or(&["night", "morning", "afternoon", "evening"]) {
+        let hours: Vec<&Hour> = d.hours.iter()
+            .filter(|h| h.time[..2].parse().is_ok_and(|hr| daypart(hr) == *part))
+            .collect();
+        if hours.is_empty() {
+            continue;
+        }
+        let avg = hours.iter().map(|h| h.temp).sum::<f64>() / hours.len() as f64;
+        let max_rp = hours.iter().map(|h| h.rp).fold(0.0, f64::max);
+        let conds: Vec<String> = hours.iter().map(|h| h.cond.clone()).collect();
+        let clouds: Vec<f64> = hours.iter().filter_map(|h| h.cloud).collect();
+        let label = match (*part, opts.lang) {
+            ("night", Lang::En) => "Night",
+            ("morning", Lang::En) => "Morning",
+            ("afternoon", Lang::En) => "Afternoon",
+            (_, Lang::En) => "Evening",
+            ("night", Lang::De) => "Nacht",
+            ("morning", Lang::De) => "Morgen",
+            ("afternoon", Lang::De) => "Nachmittag",
+            (_, Lang::De) => "Abend",
+        };
+        out!(
+            "  {label:<10} {}  {}{}°{RESET}  {}{}{RESET}",
+            pick_icon(&conds, mean(&clouds)), temp_color(avg, opts), dec(format!("{:5.1}", snap(avg, opts)), opts), rc(max_rp),
+            fmt_prob(max_rp, opts)
+        );
+    }
+}
+
+struct Report {
+    name: String,
+    lat: f64,
+    lon: f64,
+    today: NaiveDate,
+    age: Option<i64>,
+    current: Option<CurrentWeather>,
+    alerts: Vec<Alert>,
+    days: Vec<(String, DaySummary)>,
+    previous: Option<HashMap<String, cache::DayStats>>,
+    warnings: Vec<String>,
+    stations: Vec<Source>,
+}
 
+fn crosses(t: f64, opts: &Options) -> Option<bool> {
+    let t = to_unit(t, opts);
+    if opts.min_temp.is_some_and(|m| t < m) {
+        Some(true)
+    } else if opts.max_temp.is_some_and(|m| t > m) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn temp_warnings(entries: &[WeatherEntry], opts: &Options) -> Vec<String> {
+    let mut runs: Vec<(bool, NaiveDateTime, NaiveDateTime)> = Vec::new();
+    let mut last = None;
+    for e in entries {
+        let (Some(t), Some(at)) = (e.temperature, localize(&e.timestamp, opts.timezone)) else { continue };
+        let frost = crosses(t, opts);
+        match (frost, runs.last_mut()) {
+            (Some(f), Some(run)) if last == Some(f) => run.2 = at,
+            (Some(f), _) => runs.push((f, at, at)),
+            _ => {}
+        }
+        last = frost;
+    }
+    runs.into_iter()
+        .map(|(frost, from, to)| {
+            // Only name it frost or heat when the threshold actually means that, else quote the threshold
+            let limit = if frost { opts.min_temp } else { opts.max_temp }.unwrap_or_default();
+            let deg = dec(format!("{limit:.1}°"), opts);
+            let what = match (frost, opts.lang) {
+                (true, Lang::En) if limit <= to_unit(0.0, opts) => "Frost expected".to_string(),
+                (false, Lang::En) if limit >= to_unit(30.0, opts) => "Heat expected".to_string(),
+                (true, Lang::De) if limit <= to_unit(0.0, opts) => "Frost erwartet".to_string(),
+                (false, Lang::De) if limit >= to_unit(30.0, opts) => "Hitze erwartet".to_string(),
+                (true, Lang::En) => format!("Below {deg} expected"),
+                (false, Lang::En) => format!("Above {deg} expected"),
+                (true, Lang::De) => format!("Unter {deg} erwartet"),
+                (false, Lang::De) => format!("Über {deg} erwartet"),
+            };
+            let until = if to == from {
+                return format!("{what} {} {}", weekday(from.date(), opts.lang), from.format("%H:%M"));
+            } else if to.date() == from.date() {
+                to.format("%H:%M").to_string()
+            } else {
+                format!("{} {}", weekday(to.date(), opts.lang), to.format("%H:%M"))
+            };
+            format!("{what} {} {}–{until}", weekday(from.date(), opts.lang), from.format("%H:%M"))
+        })
+        .collect()
+}
+
+struct Coords {
+    lat: f64,
+    lon: f64,
+    alt: Option<f64>,
+    label: Option<String>,
+}
+
+fn parse_coords(s: &str) -> Option<Coords> {
+    let mut parts = s.splitn(3, ',');
+    let lat: f64 = parts.next()?.trim().parse().ok()?;
+    let lon: f64 = parts.next()?.trim().parse().ok()?;
+    let mut rest = parts.next().map(str::trim).unwrap_or("");
+    let mut alt = None;
+    let (first, tail) = rest.split_once(',').unwrap_or((rest, ""));
+    if let Ok(a) = first.trim().trim_end_matches('m').parse::<f64>() {
+        alt = Some(a);
+        rest = tail.trim();
+    }
+    let label = Some(rest.to_string()).filter(|l| !l.is_empty());
+    Some(Coords { lat, lon, alt, label })
+}
+
+const PLUS_ALPHABET: &str = "23456789CFGHJMPQRVWX";
+
+// Full Open Location Codes only (`8FVC9G8F+6X`); short codes need a reference point
+fn decode_plus_code(code: &str) -> Option<(f64, f64)> {
+    let code = code.trim().to_uppercase();
+    let (head, tail) = code.split_once('+')?;
+    if head.len() != 8 || tail.len() == 1 || tail.len() > 7 {
+        return None;
+    }
+    // Padding is whole pairs of '0' straight before a bare '+', and never the first pair
+    let head = head.trim_end_matches('0');
+    if head.len() < 2 || head.len() % 2 == 1 || (head.len() < 8 && !tail.is_empty()) {
+        return None;
+    }
+    let digits: Vec<usize> = head.chars().chain(tail.chars()).map(|c| PLUS_ALPHABET.find(c)).collect::<Option<_>>()?;
+    // The first pair must stay below 90°N and 180°E: at most 'C' (8 × 20°) and 'V' (17 × 20°)
+    if digits[0] > 8 || digits[1] > 17 {
+        return None;
+    }
+    let (mut lat, mut lon) = (-90.0, -180.0);
+    let mut res = 400.0;
+    for pair in digits.chunks(2).take(5) {
+        res /= 20.0;
+        lat += pair[0] as f64 * res;
+        lon += pair.get(1).copied()? as f64 * res;
+    }
+    let (mut lat_res, mut lon_res) = (res, res);
+    for &d in digits.iter().skip(10) {
+        lat_res /= 5.0;
+        lon_res /= 4.0;
+        lat += (d / 4) as f64 * lat_res;
+        lon += (d % 4) as f64 * lon_res;
+    }
+    Some((lat + lat_res / 2.0, lon + lon_res / 2.0))
+}
+
+fn is_what3words(query: &str) -> bool {
+    let words: Vec<&str> = query.trim_start_matches("///").split('.').collect();
+    words.len() == 3 && words.iter().all(|w| !w.is_empty() && w.chars().all(char::is_alphabetic))
+}
+
+fn resolve_what3words(client: &dyn HttpClient, query: &str) -> Result<(f64, f64, String), String> {
+    let key = std::env::var("W3W_API_KEY").map_err(|_| "what3words lookups need W3W_API_KEY".to_string())?;
+    let words = query.trim_start_matches("///");
+    let url = format!(
+        "https://api.what3words.com/v3/convert-to-coordinates?words={}&key={}",
+        encode_query(words), encode_query(&key)
+    );
+    let body: serde_json::Value = serde_json::from_str(&client.get(&url).map_err(|e| e.to_string())?)
+        .map_err(|e| format!("JSON error: {e}"))?;
+    let c = &body["coordinates"];
+    match (c["lat"].as_f64(), c["lng"].as_f64()) {
+        (Some(lat), Some(lon)) => {
+            let name = body["nearestPlace"].as_str().filter(|n| !n.is_empty()).map_or_else(|| format!("///{words}"), str::to_string);
+            Ok((lat, lon, name))
+        }
+        _ => Err(format!("Could not resolve what3words address: {query}")),
+    }
+}
+
+fn resolve(client: &dyn HttpClient, query: &str, opts: &Options) -> Result<(f64, f64, String), String> {
+    if opts.location_codes {
+        if let Some((lat, lon)) = decode_plus_code(query) {
+            return Ok((lat, lon, query.trim().to_uppercase()));
+        }
+        if is_what3words(query) {
+            return resolve_what3words(client, query);
+        }
+    }
+    if let Some(Coords { lat, lon, alt, label }) = parse_coords(query) {
+        let mut name = label.unwrap_or_else(|| format!("{lat:.2}, {lon:.2}"));
+        if let Some(alt) = alt {
+            if opts.verbose {
+                eprintln!("Altitude {alt} m noted but not sent, Bright Sky has no altitude parameter");
+            }
+            name += &format!(" ({alt:.0} m)");
+        }
+        return Ok((lat, lon, name));
+    }
+    let cached = if opts.refresh_geocode { None } else { cache::place(query) };
+    if let Some(place) = cached {
+        return Ok(place);
+    }
+    let found = if opts.offline { None } else { geocode(client, query, opts.verbose)? };
+    let place = found.ok_or_else(|| match (!opts.offline).then(|| suggest(client, query)).flatten() {
+        Some(name) => format!("Could not find '{query}'. Did you mean {name}?"),
+        None => format!("Could not find city: {query}"),
+    })?;
+    cache::store_place(query, &place);
+    Ok(place)
+}
+
+type FieldCheck = (&'static str, fn(&WeatherEntry) -> bool);
+
+fn missing_fields(entries: &[WeatherEntry]) -> Vec<&'static str> {
+    let checks: [FieldCheck; 4] = [
+        ("temperature", |e| e.temperature.is_some()),
+        ("precipitation_probability", |e| e.precipitation_probability.is_some()),
+        ("precipitation", |e| e.precipitation.is_some()),
+        ("wind_speed", |e| e.wind_speed.is_some()),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, present)| !entries.is_empty() && !entries.iter().any(present))
+        .map(|(field, _)| field)
+        .collect()
+}
+
+fn localize(ts: &str, tz: Option<Tz>) -> Option<NaiveDateTime> {
+    let dt = DateTime::parse_from_rfc3339(ts).ok()?;
+    Some(match tz {
+        Some(tz) => dt.with_timezone(&tz).naive_local(),
+        None => dt.naive_local(),
+    })
+}
+
+fn today_in(tz: Option<Tz>, entries: &[WeatherEntry]) -> NaiveDate {
+    let now = Utc::now();
+    match tz {
+        Some(tz) => now.with_timezone(&tz).date_naive(),
+        None => entries
+            .first()
+            .and_then(|e| DateTime::parse_from_rfc3339(&e.timestamp).ok())
+            .map_or_else(|| Local::now().date_naive(), |dt| now.with_timezone(dt.offset()).date_naive()),
+    }
+}
+
+fn fmt_offset(minutes: i64) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let (h, m) = (minutes.abs() / 60, minutes.abs() % 60);
+    if m == 0 { format!("UTC{sign}{h}") } else { format!("UTC{sign}{h}:{m:02}") }
+}
+
+// from > to wraps past midnight: each day keeps its hours from `from` on, plus the next day's up to `to`
+fn window_hours(days: &mut [(String, DaySummary)], (from, to): (u32, u32)) {
+    let hour = |h: &Hour| h.time[..2].parse::<u32>().unwrap_or(0);
+    for i in 0..days.len() {
+        let next: Vec<Hour> = match days.get(i + 1) {
+            Some((_, n)) if from > to => n.hours.iter().filter(|h| hour(h) <= to).cloned().collect(),
+            _ => Vec::new(),
+        };
+        let hours = &mut days[i].1.hours;
+        if from > to {
+            hours.retain(|h| hour(h) >= from);
+        } else {
+            hours.retain(|h| (from..=to).contains(&hour(h)));
+        }
+        hours.extend(next);
+    }
+}
+
+const MAX_ENTRIES: usize = 1000;
+const RAIN_HOUR_RP: f64 = 50.0;
+
+fn aggregate(entries: &[WeatherEntry], today: &str, opts: &Options) -> Vec<(String, DaySummary)> {
     let mut days: Vec<(String, DaySummary)> = Vec::new();
 
-    for entry in &resp.weather {
-        let day = &entry.timestamp[..10];
-        let hour = &entry.timestamp[11..16];
-        let t = entry.temperature;
-        let rp = entry.precipitation_probability.unwrap_or(0.0);
-        let cond = &entry.condition;
+    let limit = opts.max_entries.unwrap_or(MAX_ENTRIES);
+    if entries.len() > limit {
+        eprintln!("Warning: forecast has {} entries, only the first {limit} are shown (--max-entries)", entries.len());
+    }
+    let entries = &entries[..entries.len().min(limit)];
+    let mut seen = std::collections::HashSet::new();
+    let mut last_offset: Option<(String, i64)> = None;
+
+    for entry in entries {
+        let Some(local) = localize(&entry.timestamp, opts.timezone) else {
+            if opts.verbose {
+                eprintln!("Warning: skipping entry with invalid timestamp {}", entry.timestamp);
+            }
+            continue;
+        };
+        let day = &local.format("%Y-%m-%d").to_string();
+        let hour = local.format(if opts.exact_minutes { "%H:%M" } else { "%H:00" }).to_string();
+        let Some(t) = entry.temperature else {
+            if opts.verbose {
+                eprintln!("Warning: skipping {} without temperature", entry.timestamp);
+            }
+            continue;
+        };
+        let utc = DateTime::parse_from_rfc3339(&entry.timestamp).ok().map(|t| t.naive_utc());
+        // Dedup on the row the record lands in: 14:00 and 14:30 share the 14:00 row unless --exact-minutes
+        let bucket = utc.and_then(|u| if opts.exact_minutes { Some(u) } else { u.with_minute(0)?.with_second(0) });
+        if !seen.insert(bucket) {
+            if opts.verbose {
+                eprintln!("Warning: skipping duplicate record for {}", entry.timestamp);
+            }
+            continue;
+        }
+        let raw_rp = entry.precipitation_probability.unwrap_or(0.0);
+        let rp = raw_rp.clamp(0.0, 100.0);
+        if rp != raw_rp && opts.verbose {
+            eprintln!("Warning: rain probability {raw_rp}% at {} clamped to {rp}%", entry.timestamp);
+        }
+        let cond = entry.condition.as_deref().unwrap_or("dry");
 
         let idx = days.iter().position(|(d, _)| d == day);
         let summary = if let Some(i) = idx {
@@ -147,55 +2157,1573 @@ fn main() {
                 hi: f64::NEG_INFINITY,
                 lo: f64::INFINITY,
                 max_rp: 0.0,
+                max_wind: None,
                 conds: Vec::new(),
+                cond_hours: HashMap::new(),
+                clouds: Vec::new(),
+                humidity: Vec::new(),
+                temps: Vec::new(),
+                rain_hours: 0,
+                anomaly: None,
+                dst_note: None,
+                sun: None,
                 hours: Vec::new(),
             }));
             &mut days.last_mut().unwrap().1
         };
 
+        let offset = utc.map(|u| (local - u).num_minutes());
+        if let (Some(off), Some((prev_day, prev))) = (offset, &last_offset)
+            && prev_day == day
+            && *prev != off
+        {
+            let change = format!("{} → {}", fmt_offset(*prev), fmt_offset(off));
+            summary.dst_note = Some(match opts.lang {
+                Lang::En => format!("Clocks change at {hour} ({change})"),
+                Lang::De => format!("Zeitumstellung um {hour} ({change})"),
+            });
+        }
+        if let Some(off) = offset {
+            last_offset = Some((day.to_string(), off));
+        }
+
         if t > summary.hi { summary.hi = t; }
         if t < summary.lo { summary.lo = t; }
         if rp > summary.max_rp { summary.max_rp = rp; }
-        if cond != "dry" && !summary.conds.contains(cond) {
-            summary.conds.push(cond.clone());
+        if let Some(w) = entry.wind_speed {
+            summary.max_wind = Some(summary.max_wind.map_or(w, |m| m.max(w)));
+        }
+        summary.clouds.extend(entry.cloud_cover);
+        summary.humidity.extend(entry.relative_humidity);
+        summary.temps.push(t);
+        *summary.cond_hours.entry(cond.to_string()).or_insert(0) += 1;
+        if rp >= RAIN_HOUR_RP || is_wet(cond) {
+            summary.rain_hours += 1;
+        }
+        if cond != "dry" && !summary.conds.iter().any(|c| c == cond) {
+            summary.conds.push(cond.to_string());
+        }
+        if day == today || opts.tui || opts.hour_range.is_some_and(|(from, to)| from > to) {
+            summary.hours.push(Hour {
+                time: hour,
+                temp: t,
+                rp,
+                cond: cond.to_string(),
+                wind: entry.wind_speed,
+                precip: entry.precipitation,
+                cloud: entry.cloud_cover,
+                visibility: entry.visibility,
+                storm: entry.thunderstorm_probability,
+                utc,
+            });
+        }
+    }
+    days
+}
+
+fn overview_line(days: &[(String, DaySummary)], opts: &Options) -> Option<String> {
+    days.iter().reduce(|a, b| if b.1.max_rp > a.1.max_rp { b } else { a }).map(|wet| {
+        let lo = days.iter().map(|(_, d)| d.lo).fold(f64::INFINITY, f64::min);
+        let hi = days.iter().map(|(_, d)| d.hi).fold(f64::NEG_INFINITY, f64::max);
+        let wet_day = NaiveDate::parse_from_str(&wet.0, "%Y-%m-%d")
+            .map(|dt| weekday(dt, opts.lang).to_string())
+            .unwrap_or_else(|_| wet.0.clone());
+        format!(
+            "  {DIM}{}-day range:{RESET} {}{}°{RESET} … {}{}°{RESET}{DIM}, max rain{RESET} {}{}{RESET} {DIM}({wet_day}){RESET}",
+            days.len(), temp_color(lo, opts), dec(format!("{:.1}", snap(lo, opts)), opts),
+            temp_color(hi, opts), dec(format!("{:.1}", snap(hi, opts)), opts),
+            rc(wet.1.max_rp), fmt_prob(wet.1.max_rp, opts).trim_start()
+        )
+    })
+}
+
+static PREFETCHES: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+const PREFETCH_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+static TIMINGS: Mutex<Vec<(&str, std::time::Duration)>> = Mutex::new(Vec::new());
+
+fn timed<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = std::time::Instant::now();
+    let value = f();
+    if let Ok(mut t) = TIMINGS.lock() {
+        t.push((phase, start.elapsed()));
+    }
+    value
+}
+
+fn print_timings() {
+    let Ok(mut timings) = TIMINGS.lock() else { return };
+    for phase in ["geocoding", "fetch", "parsing", "rendering"] {
+        let total: std::time::Duration = timings.iter().filter(|(p, _)| *p == phase).map(|(_, d)| *d).sum();
+        eprintln!("{phase:<10} {:>8.1} ms", total.as_secs_f64() * 1000.0);
+    }
+    timings.clear();
+}
+
+// Bright Sky only knows `dwd` (°C, km/h, mm, m) and `si` (K, m/s, kg/m²), there is no imperial set,
+// so pin `dwd` and do --units imperial and --wind-scale client-side
+const BRIGHTSKY_UNITS: &str = "dwd";
+
+fn weather_url(lat: f64, lon: f64, from: &str, to: &str, opts: &Options) -> String {
+    let mut url = format!("https://api.brightsky.dev/weather?lat={lat}&lon={lon}&date={from}&last_date={to}&units={BRIGHTSKY_UNITS}");
+    if let Some(tz) = opts.response_tz {
+        url += &format!("&tz={}", encode_query(tz.name()));
+    }
+    url
+}
+
+const MAX_FOLLOW_UPS: usize = 3;
+
+fn last_of(v: &serde_json::Value) -> Option<DateTime<chrono::FixedOffset>> {
+    v["weather"].as_array()?.last()?["timestamp"].as_str().and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+}
+
+// Bright Sky caps long windows; keep requesting from the last record until `end` is covered
+fn fill_window(
+    client: &dyn HttpClient,
+    lat: f64,
+    lon: f64,
+    body: String,
+    end: DateTime<Local>,
+    widened: bool,
+    opts: &Options,
+) -> String {
+    let Ok(mut v) = serde_json::from_str::<serde_json::Value>(&body) else { return body };
+    let mut extended = false;
+    for _ in 0..MAX_FOLLOW_UPS {
+        let Some(last) = last_of(&v) else { break };
+        if last + chrono::Duration::hours(1) > end {
+            break;
+        }
+        let from = (last + chrono::Duration::hours(1)).with_timezone(&Local).format("%Y-%m-%dT%H:%M").to_string();
+        let mut url = weather_url(lat, lon, &from, &end.format("%Y-%m-%dT%H:00").to_string(), opts);
+        if widened {
+            url += &format!("&max_dist={}", WIDE_MAX_DIST_KM * 1000);
+        }
+        let more = client.get(&url).ok().and_then(|b| serde_json::from_str::<serde_json::Value>(&b).ok());
+        let Some(serde_json::Value::Array(rows)) = more.map(|mut m| m["weather"].take()) else { break };
+        if rows.is_empty() {
+            break;
+        }
+        if let Some(w) = v["weather"].as_array_mut() {
+            w.extend(rows);
+        }
+        extended = true;
+    }
+    if extended { v.to_string() } else { body }
+}
+
+// Same fetch path as the foreground, on its own client so the exchanges land in their own --log record
+fn prefetch(lat: f64, lon: f64, end: DateTime<Local>, opts: &Options) {
+    let from = Local::now().format("%Y-%m-%dT%H:00").to_string();
+    let until = end.format("%Y-%m-%dT%H:00").to_string();
+    let url = weather_url(lat, lon, &from, &until, opts);
+    let bg = Options { response_tz: opts.response_tz, ..Options::default() };
+    let (verbose, log_bodies, log) = (opts.verbose, opts.log_bodies, opts.log.clone());
+    let handle = std::thread::spawn(move || {
+        let client = UreqClient::new(verbose, log_bodies);
+        if let Ok((b, widened)) = fetch_forecast(&client, &url) {
+            let b = fill_window(&client, lat, lon, b, end, widened, &bg);
+            cache::store_forecast(lat, lon, &until, bg.response_tz.map(|t| t.name()), &b);
+        }
+        if let Some(path) = log {
+            let record = serde_json::json!({ "timestamp": Utc::now().to_rfc3339(), "prefetch": true, "requests": *client.exchanges() });
+            if let Err(e) = append_line(&path, &record.to_string()) {
+                eprintln!("Warning: could not write log {path}: {e}");
+            }
+        }
+    });
+    if let Ok(mut handles) = PREFETCHES.lock() {
+        handles.push(handle);
+    }
+}
+
+// Give running prefetches a moment to land in the cache, but never hold up quitting on a slow network
+fn finish_prefetches() {
+    let Ok(mut handles) = PREFETCHES.lock() else { return };
+    let deadline = std::time::Instant::now() + PREFETCH_GRACE;
+    while handles.iter().any(|h| !h.is_finished()) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    handles.clear();
+}
+
+const FORECAST_DAYS: i64 = 3;
+const MAX_DAYS_FROM_NOW: i64 = 16;
+
+fn window_days(days: i64, opts: &Options) -> i64 {
+    let cap = opts.max_days_from_now.unwrap_or(MAX_DAYS_FROM_NOW);
+    if days > cap {
+        eprintln!("Warning: {days}-day window clamped to {cap} days (--max-days-from-now)");
+    }
+    days.min(cap)
+}
+
+fn load_report(client: &dyn HttpClient, lat: f64, lon: f64, name: &str, opts: &Options) -> Result<Report, String> {
+    let now = Local::now();
+    let date_from = now.format("%Y-%m-%dT%H:00").to_string();
+    let days = window_days(opts.days.unwrap_or(FORECAST_DAYS), opts);
+    let end = now + chrono::Duration::days(days);
+    let date_to = if opts.today_only {
+        format!("{}T23:59", now.format("%Y-%m-%d"))
+    } else {
+        end.format("%Y-%m-%dT%H:00").to_string()
+    };
+
+    let url = weather_url(lat, lon, &date_from, &date_to, opts);
+
+    let tz = opts.response_tz.map(|t| t.name());
+    // Offline, any cached window beats nothing
+    // Under --watch/--refresh with --prefetch, last round's background copy stands in for a fetch
+    let interval = opts.refresh.map(|(_, secs)| secs).or(opts.watch).filter(|_| opts.prefetch);
+    let max_age = opts.max_age.or(interval.map(|secs| secs.div_ceil(60) as i64 + 1));
+    let cached = cache::forecast(lat, lon, (!opts.offline).then_some(date_to.as_str()), tz).filter(|(_, age)| {
+        opts.offline || max_age.is_some_and(|max| *age < max)
+    });
+    let ahead = (days + 1).min(opts.max_days_from_now.unwrap_or(MAX_DAYS_FROM_NOW));
+    let wider = now + chrono::Duration::days(ahead);
+    let (body, age) = match cached {
+        Some((b, age)) => {
+            if interval.is_some() && !opts.offline {
+                prefetch(lat, lon, wider, opts);
+            }
+            (b, Some(age))
+        }
+        None if opts.offline => return Err(format!("No cached forecast for {name}")),
+        None => match timed("fetch", || fetch_forecast(client, &url)) {
+            Ok((b, widened)) => {
+                let b = if opts.today_only { b } else { fill_window(client, lat, lon, b, end, widened, opts) };
+                let last = serde_json::from_str(&b).ok().as_ref().and_then(last_of);
+                if !opts.today_only && let Some(last) = last && last + chrono::Duration::hours(1) <= end {
+                    eprintln!("Warning: forecast truncated by the provider, data ends at {}", last.with_timezone(&Local).format("%a %H:%M"));
+                }
+                if widened {
+                    eprintln!("{DIM}No station nearby, widened search radius to {WIDE_MAX_DIST_KM} km{RESET}");
+                }
+                cache::store_forecast(lat, lon, &date_to, tz, &b);
+                if opts.prefetch {
+                    prefetch(lat, lon, wider, opts);
+                }
+                (b, None)
+            }
+            Err(e) => return Err(e.to_string()),
+        },
+    };
+
+    let current = if opts.now && !opts.offline {
+        let url = format!("https://api.brightsky.dev/current_weather?lat={lat}&lon={lon}&units={BRIGHTSKY_UNITS}");
+        let parsed = client.get(&url).map_err(|e| e.to_string()).and_then(|b| {
+            serde_json::from_str::<CurrentResponse>(&b).map_err(|e| format!("JSON error: {e}"))
+        });
+        match parsed {
+            Ok(mut c) => {
+                if let Some(cond) = &opts.condition_override {
+                    c.weather.condition = Some(cond.clone());
+                }
+                c.weather.temperature = opts.temp_override.or(c.weather.temperature);
+                Some(c.weather)
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let alerts = if opts.alerts && !opts.offline {
+        fetch_alerts(client, lat, lon).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    let mut r = timed("parsing", || build_report(&body, lat, lon, name, Some(end), opts))?;
+    r.previous = cache::summary(lat, lon);
+    let stats = r.days.iter().map(|(day, d)| (day.clone(), (d.hi, d.lo, d.max_rp))).collect();
+    cache::store_summary(lat, lon, &stats);
+    r.age = age;
+    r.current = current;
+    r.alerts = alerts;
+    Ok(r)
+}
+
+fn stdin_report(opts: &Options) -> Result<Report, String> {
+    let body = std::io::read_to_string(std::io::stdin()).map_err(|e| format!("Could not read stdin: {e}"))?;
+    let name = opts.label.clone().unwrap_or_else(|| "stdin".to_string());
+    build_report(&body, f64::NAN, f64::NAN, &name, None, opts)
+}
+
+fn build_report(
+    body: &str,
+    lat: f64,
+    lon: f64,
+    name: &str,
+    end: Option<DateTime<Local>>,
+    opts: &Options,
+) -> Result<Report, String> {
+    let mut resp: Response = serde_json::from_str(body).map_err(|e| format!("JSON error: {e}"))?;
+    if let Some(end) = end {
+        resp.weather.retain(|e| DateTime::parse_from_rfc3339(&e.timestamp).map_or(true, |t| t <= end));
+    }
+    let (lat, lon) = match resp.sources.first() {
+        Some(s) if lat.is_nan() => (s.lat, s.lon),
+        _ => (lat, lon),
+    };
+    if resp.weather.is_empty() {
+        return Err(format!("No weather data available for {name}"));
+    }
+    for e in &mut resp.weather {
+        if let Some(c) = &opts.condition_override {
+            e.condition = Some(c.clone());
+        }
+        e.temperature = opts.temp_override.or(e.temperature);
+        e.precipitation_probability = opts.rain_override.or(e.precipitation_probability);
+    }
+    if opts.strict {
+        for field in missing_fields(&resp.weather) {
+            eprintln!("Warning: no record has `{field}`, the Bright Sky schema may have changed");
+        }
+    }
+
+    let today = match end {
+        Some(_) => today_in(opts.timezone, &resp.weather),
+        None => resp.weather.iter().find_map(|e| localize(&e.timestamp, opts.timezone)).map_or_else(
+            || today_in(opts.timezone, &resp.weather),
+            |t| t.date(),
+        ),
+    };
+    let today_key = today.format("%Y-%m-%d").to_string();
+    let mut days = aggregate(&resp.weather, &today_key, opts);
+    if let Some(range) = opts.hour_range {
+        window_hours(&mut days, range);
+    }
+    if opts.today_only {
+        days.retain(|(day, _)| *day == today_key);
+    }
+    for (day, d) in &mut days {
+        d.sun = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok().and_then(|date| sun_times(date, lat, lon));
+        if opts.anomaly && *day == today_key && !lat.is_nan() {
+            d.anomaly = Some((d.hi + d.lo) / 2.0 - seasonal_normal(today.month0() as usize, lat));
+        }
+    }
+
+    // Warmest first for hi, driest first for rain; ties keep date order
+    match opts.sort {
+        SortKey::Date => {}
+        SortKey::Hi => days.sort_by(|a, b| b.1.hi.total_cmp(&a.1.hi).then_with(|| a.0.cmp(&b.0))),
+        SortKey::Rain => days.sort_by(|a, b| a.1.max_rp.total_cmp(&b.1.max_rp).then_with(|| a.0.cmp(&b.0))),
+    }
+    if opts.today_first && let Some(i) = days.iter().position(|(day, _)| *day == today_key) {
+        days[..=i].rotate_right(1);
+    }
+
+    let warnings = temp_warnings(&resp.weather, opts);
+    let stations: Vec<Source> = resp.sources.into_iter()
+        .filter(|s| resp.weather.iter().any(|e| e.source_id == Some(s.id)))
+        .collect();
+    if opts.verbose {
+        for s in &stations {
+            eprintln!(
+                "Station: {} (DWD {}, WMO {}, {:.1} km away)",
+                s.station_name.as_deref().unwrap_or("?"),
+                s.dwd_station_id.as_deref().unwrap_or("-"),
+                s.wmo_station_id.as_deref().unwrap_or("-"),
+                s.distance.unwrap_or(0.0) / 1000.0
+            );
+        }
+    }
+    Ok(Report {
+        name: name.to_string(),
+        lat,
+        lon,
+        today,
+        age: None,
+        current: None,
+        alerts: Vec::new(),
+        days,
+        previous: None,
+        warnings,
+        stations,
+    })
+}
+
+fn print_diff(r: &Report, opts: &Options) {
+    let Some(prev) = &r.previous else {
+        out!("  {DIM}No previous run to compare against{RESET}");
+        return;
+    };
+    for offset in 0..2 {
+        let date = r.today + chrono::Duration::days(offset);
+        let key = date.format("%Y-%m-%d").to_string();
+        let current = r.days.iter().find(|(day, _)| *day == key);
+        let (Some((_, d)), Some(&(hi, lo, rp))) = (current, prev.get(&key)) else { continue };
+        let mut changes = Vec::new();
+        let (hi, lo, d_hi, d_lo) = (to_unit(hi, opts), to_unit(lo, opts), to_unit(d.hi, opts), to_unit(d.lo, opts));
+        for (what, old, new, unit) in [("hi", hi, d_hi, "°"), ("lo", lo, d_lo, "°"), ("rain", rp, d.max_rp, "%")] {
+            if (new - old).abs() >= 0.5 {
+                changes.push(format!("{what} {old:.0}{unit}→{new:.0}{unit} ({:+.0})", new - old));
+            }
         }
-        if day == today {
-            summary.hours.push((hour.to_string(), t, rp, cond.clone()));
+        let label = day_label(date, r.today, opts);
+        let width = label_width(r.today, opts);
+        if changes.is_empty() {
+            out!("  {label:<width$} {DIM}unchanged{RESET}");
+        } else {
+            out!("  {label:<width$} {}", changes.join("  "));
+        }
+    }
+    out!();
+}
+
+fn verdict(d: &DaySummary) -> (&'static str, &'static str, &'static str) {
+    if d.max_rp >= 90.0 || d.hi >= 35.0 || d.lo <= -10.0 {
+        (RED, "Stay in", "Lieber drinnen bleiben")
+    } else if d.max_rp >= 40.0 {
+        (YELLOW, "Bring an umbrella", "Regenschirm mitnehmen")
+    } else if d.hi >= 30.0 || d.lo <= 0.0 {
+        (YELLOW, "Dress for the temperature", "Passend anziehen")
+    } else {
+        (GREEN, "Good day to go out", "Guter Tag für draußen")
+    }
+}
+
+fn print_report(r: &Report, opts: &Options) {
+    let coords = format!("{:.2}, {:.2}", r.lat, r.lon);
+    // A location typed as coordinates is already named by them
+    let name = if opts.show_coords && !r.name.starts_with(&coords) { format!("{} ({coords})", r.name) } else { r.name.clone() };
+    match r.age {
+        Some(m) => out!("\n  {BOLD}{CYAN}{name}{RESET}  {DIM}(cached {m}m ago){RESET}"),
+        None => out!("\n  {BOLD}{CYAN}{name}{RESET}"),
+    }
+    let nearest = r.stations.iter().filter_map(|s| s.distance).fold(f64::INFINITY, f64::min) / 1000.0;
+    if nearest.is_finite() && nearest > opts.max_station_distance.unwrap_or(DEFAULT_STATION_DISTANCE_KM) {
+        match opts.lang {
+            Lang::En => out!("  {DIM}⚠ nearest station is {nearest:.0} km away{RESET}"),
+            Lang::De => out!("  {DIM}⚠ nächste Station ist {nearest:.0} km entfernt{RESET}"),
         }
     }
+    print_alerts(&r.alerts, opts);
+    for w in &r.warnings {
+        out!("  {MAGENTA}⚠️ {w}{RESET}");
+    }
+    if !r.warnings.is_empty() {
+        out!();
+    }
+    if let Some(cur) = &r.current {
+        print_current(cur, opts);
+        out!();
+    }
+    if opts.diff {
+        print_diff(r, opts);
+    }
+    if opts.overview && let Some(line) = overview_line(&r.days, opts) {
+        out!("{line}");
+    }
+    print_cards(&r.days, r.today, opts);
 
-    // Cards
-    println!("\n  {BOLD}{CYAN}{name}{RESET}");
-    println!("  {DIM}                 Temp             Rain{RESET}");
-    println!("  {DIM}──────────────────────────────────────{RESET}");
-    for (day, d) in &days {
-        let ic = pick_icon(&d.conds);
-        let label = if day == &today {
-            format!("{BOLD}Today{RESET}     ")
+    let today = r.today.format("%Y-%m-%d").to_string();
+    if let Some((_, d)) = r.days.iter().find(|(day, _)| day == &today)
+        && !d.hours.is_empty()
+    {
+        if opts.interval_summary {
+            print_dayparts(d, opts);
+        } else if let Some(col) = opts.width_per_hour {
+            print_strip(d, col, opts);
         } else {
-            let dt = NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
-            format!("{:<10}", dt.format("%a %d.%m."))
+            print_hourly(d, opts);
+        }
+    }
+    if opts.condition_stats && !r.days.is_empty() {
+        out!();
+        out!("  {DIM}{}{RESET}", condition_stats(&r.days, opts.lang));
+    }
+    if opts.status_line && let Some((_, d)) = r.days.iter().find(|(day, _)| day == &today) {
+        let (color, en, de) = verdict(d);
+        let text = if opts.lang == Lang::De { de } else { en };
+        out!();
+        out!("  {color}●{RESET} {BOLD}{text}{RESET}");
+    }
+    out!();
+}
+
+fn print_brief(r: &Report, opts: &Options) {
+    let prefix = if opts.file.is_some() { format!("{} ", r.name) } else { String::new() };
+    for (day, d) in &r.days {
+        let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else { continue };
+        out!(
+            "{prefix}{} {} {}/{} {}",
+            weekday(date, opts.lang), pick_icon(&d.conds, mean(&d.clouds)),
+            dec(format!("{:.0}", snap(d.lo, opts)), opts), dec(format!("{:.0}", snap(d.hi, opts)), opts), fmt_prob(d.max_rp, opts).trim_start()
+        );
+    }
+}
+
+fn print_plain(r: &Report, opts: &Options) {
+    match opts.lang {
+        Lang::En => out!("Weather for {}.", r.name),
+        Lang::De => out!("Wetter für {}.", r.name),
+    }
+    for (day, d) in &r.days {
+        let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else { continue };
+        let name = match (date - r.today).num_days() {
+            0 | 1 => day_label(date, r.today, opts),
+            _ => weekday_long(date, opts.lang).to_string(),
         };
-        println!(
-            "  {label} {ic}  {}{:5.1}°{RESET}  …  {}{:5.1}°{RESET}  {}{:3.0}%{RESET}",
-            tc(d.lo), d.lo, tc(d.hi), d.hi, rc(d.max_rp), d.max_rp
+        let hi = dec(format!("{:.0}", snap(d.hi, opts)), opts);
+        let lo = dec(format!("{:.0}", snap(d.lo, opts)), opts);
+        let cond = condition_name(dominant(&d.conds), opts.lang);
+        match opts.lang {
+            Lang::En => out!(
+                "{name}: high {hi} degrees, low {lo} degrees, {} chance of rain, {cond}.",
+                fmt_chance(d.max_rp, " percent", opts)
+            ),
+            Lang::De => out!(
+                "{name}: Höchstwert {hi} Grad, Tiefstwert {lo} Grad, {} Regenwahrscheinlichkeit, {cond}.",
+                fmt_chance(d.max_rp, " Prozent", opts)
+            ),
+        }
+    }
+}
+
+fn is_wet(cond: &str) -> bool {
+    matches!(cond, "rain" | "sleet" | "snow" | "hail" | "thunderstorm")
+}
+
+fn transitions(hours: &[Hour], lang: Lang) -> Vec<String> {
+    let start = hours.windows(2).find(|w| !is_wet(&w[0].cond) && is_wet(&w[1].cond)).map(|w| &w[1]);
+    let after = start.map_or(hours, |h| &hours[hours.iter().position(|x| std::ptr::eq(x, h)).unwrap_or(0)..]);
+    let stop = after.windows(2).find(|w| is_wet(&w[0].cond) && !is_wet(&w[1].cond)).map(|w| &w[1]);
+    let mut out = Vec::new();
+    if let Some(h) = start {
+        let name = condition_name(&h.cond, lang);
+        out.push(match lang {
+            Lang::En => format!("{name} starting around {}", h.time),
+            Lang::De => format!("{name} ab etwa {}", h.time),
+        });
+    }
+    if let Some(h) = stop {
+        out.push(match lang {
+            Lang::En => format!("clearing up after {}", h.time),
+            Lang::De => format!("Aufklaren ab {}", h.time),
+        });
+    }
+    out
+}
+
+fn print_summary(r: &Report, opts: &Options) {
+    let today = r.today.format("%Y-%m-%d").to_string();
+    let Some((_, d)) = r.days.iter().find(|(day, _)| *day == today) else { return };
+    let (hi, lo) = (dec(format!("{:.0}", snap(d.hi, opts)), opts), dec(format!("{:.0}", snap(d.lo, opts)), opts));
+    let cond = condition_name(dominant(&d.conds), opts.lang);
+    let mut text = match opts.lang {
+        Lang::En => format!("Today in {}: {lo}–{hi}°, {cond}, {} chance of rain.", r.name, fmt_chance(d.max_rp, "%", opts)),
+        Lang::De => format!("Heute in {}: {lo}–{hi}°, {cond}, {} Regenwahrscheinlichkeit.", r.name, fmt_chance(d.max_rp, " %", opts)),
+    };
+    let changes = transitions(&d.hours, opts.lang);
+    if !changes.is_empty() {
+        let joined = changes.join(", ");
+        let mut chars = joined.chars();
+        let first = chars.next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default();
+        text += &format!(" {first}{}.", chars.as_str());
+    }
+    out!("{text}");
+}
+
+fn ics_text(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// RFC 5545 §3.1: content lines longer than 75 octets continue on the next line after CRLF + space
+fn ics_fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            out += "\r\n ";
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out
+}
+
+fn ics_events(r: &Report, stamp: &str, opts: &Options) -> Vec<String> {
+    let mut events = Vec::new();
+    for (day, d) in &r.days {
+        let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else { continue };
+        let next = date.succ_opt().unwrap_or(date);
+        let title = format!(
+            "{} {}°/{}° · {} rain",
+            pick_icon(&d.conds, mean(&d.clouds)),
+            dec(format!("{:.0}", snap(d.hi, opts)), opts),
+            dec(format!("{:.0}", snap(d.lo, opts)), opts),
+            fmt_chance(d.max_rp, "%", opts)
         );
+        events.push([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{:.2}-{:.2}@weather-cli", date.format("%Y%m%d"), r.lat, r.lon),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+            format!("SUMMARY:{}", ics_text(&title)),
+            format!("LOCATION:{}", ics_text(&r.name)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ].map(|l| ics_fold(&l)).join("\r\n"));
+    }
+    events
+}
+
+fn write_ics(path: &str, events: &[String]) -> std::io::Result<()> {
+    let mut text = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//weather-cli//EN\r\n");
+    for e in events {
+        text += e;
+        text += "\r\n";
+    }
+    text += "END:VCALENDAR\r\n";
+    std::fs::write(path, text)
+}
+
+const METRICS: [(&str, &str); 5] = [
+    ("weather_temperature_celsius", "Current temperature, or the nearest forecast hour"),
+    ("weather_temperature_high_celsius", "Highest temperature forecast for today"),
+    ("weather_temperature_low_celsius", "Lowest temperature forecast for today"),
+    ("weather_rain_probability", "Highest rain probability today, 0 to 1"),
+    ("weather_wind_speed_kmh", "Highest wind speed forecast for today"),
+];
+
+fn metric_values(r: &Report) -> Vec<Option<f64>> {
+    let today = r.today.format("%Y-%m-%d").to_string();
+    let Some((_, d)) = r.days.iter().find(|(day, _)| *day == today) else { return vec![None; METRICS.len()] };
+    let now = Utc::now().naive_utc();
+    let nearest = d.hours.iter().filter_map(|h| Some(((h.utc? - now).num_minutes().abs(), h.temp))).min_by_key(|&(gap, _)| gap);
+    let current = r.current.as_ref().and_then(|c| c.temperature).or(nearest.map(|(_, t)| t));
+    vec![current, Some(d.hi), Some(d.lo), Some(d.max_rp / 100.0), d.max_wind]
+}
+
+fn write_metrics(path: &str, reports: &[(String, Vec<Option<f64>>)]) -> std::io::Result<()> {
+    let mut text = String::new();
+    for (i, (metric, help)) in METRICS.iter().enumerate() {
+        text += &format!("# HELP {metric} {help}\n# TYPE {metric} gauge\n");
+        for (city, values) in reports {
+            if let Some(v) = values[i] {
+                let city = city.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+                text += &format!("{metric}{{city=\"{city}\"}} {v}\n");
+            }
+        }
+    }
+    // The textfile collector may read at any time, so never leave a half-written file behind
+    let tmp = format!("{path}.tmp");
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn notify(r: &Report, opts: &Options) -> Result<(), String> {
+    let today = r.today.format("%Y-%m-%d").to_string();
+    let (_, d) = r.days.iter().find(|(day, _)| *day == today).ok_or("no forecast for today")?;
+    let summary = format!("{} {}", pick_icon(&d.conds, mean(&d.clouds)), r.name);
+    let body = format!(
+        "{}° / {}° · {} rain",
+        dec(format!("{:.0}", snap(d.hi, opts)), opts),
+        dec(format!("{:.0}", snap(d.lo, opts)), opts),
+        fmt_chance(d.max_rp, "%", opts)
+    );
+    notify_rust::Notification::new()
+        .appname("weather")
+        .summary(&summary)
+        .body(&body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn append_line(path: &str, line: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+fn read_locations(path: &str) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {path}: {e}"))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn seed_cache(client: &dyn HttpClient, path: &str, opts: &Options) -> Result<usize, String> {
+    let cities = read_locations(path)?;
+    let (mut ok, mut failed, mut cached) = (0, 0, 0);
+    for city in &cities {
+        if !opts.refresh_geocode && cache::place(city).is_some() {
+            cached += 1;
+            continue;
+        }
+        match geocode(client, city, opts.verbose) {
+            Ok(Some(place)) => {
+                cache::store_place(city, &place);
+                ok += 1;
+            }
+            Ok(None) => {
+                eprintln!("Could not find city: {city}");
+                failed += 1;
+            }
+            Err(e) => {
+                eprintln!("{city}: {e}");
+                failed += 1;
+            }
+        }
+    }
+    eprintln!("Seeded {ok} places, {failed} failed, {cached} already cached");
+    Ok(failed)
+}
+
+fn main() {
+    let opts = match parse_args() {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+    let no_color = opts.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if let Some(w) = opts.max_width {
+        MAX_WIDTH.store(w, Ordering::Relaxed);
+    }
+    let linux_console = std::env::var("TERM").is_ok_and(|t| t == "linux");
+    TEXT_GLYPHS.store(opts.no_variation_selector || linux_console, Ordering::Relaxed);
+    let looping = opts.watch.is_some() || opts.refresh.is_some_and(|(n, _)| n > 1);
+    if opts.pager && !looping && opts.format == Format::Text && std::io::stdout().is_terminal() {
+        SINK.set(Some(String::new()));
+    }
+    let ansi = std::io::stdout().is_terminal() && ansi_supported() && !opts.strip_ansi;
+    // --strip-ansi wins over --force-color: every line is rendered in color, then stripped in emit()
+    COLOR.store(!no_color && !opts.strip_ansi && (opts.force_color || ansi), Ordering::Relaxed);
+
+    if let Some(cond) = &opts.describe {
+        out!("{} {}: {}", icon(cond), condition_name(cond, opts.lang), condition_description(cond));
+        return;
+    }
+    if opts.legend {
+        print_legend(opts.lang);
+        return;
     }
 
-    // Hourly today
-    if let Some((_, d)) = days.iter().find(|(day, _)| day == &today) {
-        if !d.hours.is_empty() {
-            println!();
-            println!("  {DIM}Time         Temp   Rain{RESET}");
-            println!("  {DIM}──────────────────────────────────────{RESET}");
-            for (hour, t, rp, cond) in &d.hours {
-                let ic = icon(cond);
-                println!(
-                    "  {hour}  {ic}  {}{:5.1}°{RESET}  {}{:3.0}%{RESET}",
-                    tc(*t), t, rc(*rp), rp
-                );
+    let client = UreqClient::new(opts.verbose, opts.log_bodies);
+    if let Some(path) = &opts.seed_cache {
+        match seed_cache(&client, path, &opts) {
+            Ok(0) => return,
+            Ok(_) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(2);
             }
         }
     }
-    println!();
+    let queries = if opts.stdin_json {
+        vec![String::new()]
+    } else if let Some(path) = &opts.file {
+        match read_locations(path) {
+            Ok(q) => q,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(2);
+            }
+        }
+    } else if opts.city.is_empty() && opts.auto_locate {
+        match locate_by_ip(&client) {
+            Ok((lat, lon, name)) => {
+                eprintln!("Detected location from your IP address: {name} ({lat:.2}, {lon:.2})");
+                vec![format!("{lat},{lon},{name}")]
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        if opts.city.is_empty() && (opts.no_prompt || !std::io::stdin().is_terminal()) {
+            eprintln!("No city given");
+            std::process::exit(2);
+        }
+        let city = if opts.city.is_empty() {
+            eprint!("City: ");
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input).unwrap();
+            input.trim().to_string()
+        } else {
+            opts.city.clone()
+        };
+        vec![city]
+    };
+
+    let (rounds, interval) = match (opts.refresh, opts.watch) {
+        (Some((n, secs)), _) => (Some(n), secs),
+        (None, Some(secs)) => (None, secs),
+        (None, None) => (Some(1), 0),
+    };
+    let mut round = 0;
+    loop {
+        round += 1;
+        if round > 1 && opts.format == Format::Text && ansi {
+            write_stdout("\x1b[2J\x1b[H");
+            PRINTED.store(0, Ordering::Relaxed);
+        }
+        run(&client, &queries, &opts);
+        if rounds.is_some_and(|n| round >= n) || STDOUT_CLOSED.load(Ordering::Relaxed) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+        client.clear();
+    }
+    if let Some(text) = SINK.take() {
+        page(&text);
+    }
+    finish_prefetches();
+}
+
+fn run(client: &UreqClient, queries: &[String], opts: &Options) {
+    let mut json = Vec::new();
+    let mut resolved = Vec::new();
+    let mut events = Vec::new();
+    let mut metrics = Vec::new();
+    let mut json_file = Vec::new();
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    for query in queries {
+        let report = if opts.stdin_json {
+            stdin_report(opts)
+        } else {
+            timed("geocoding", || resolve(client, query, opts)).and_then(|(lat, lon, name)| {
+                resolved.push(serde_json::json!({ "query": query, "name": name, "lat": lat, "lon": lon }));
+                load_report(client, lat, lon, &name, opts)
+            })
+        };
+        if let (Some(_), Ok(r)) = (&opts.export_ics, &report) {
+            events.extend(ics_events(r, &stamp, opts));
+        }
+        if let (Some(_), Ok(r)) = (&opts.emit_metrics, &report) {
+            metrics.push((r.name.clone(), metric_values(r)));
+        }
+        if let (Some(_), Ok(r)) = (&opts.output_json, &report) {
+            json_file.push(json_output(r, opts));
+        }
+        timed("rendering", || match report {
+            Ok(r) if opts.format == Format::Json => json.push(json_output(&r, opts)),
+            Ok(r) if opts.notify => {
+                if let Err(e) = notify(&r, opts) {
+                    eprintln!("Warning: could not send notification ({e}), printing instead");
+                    print_report(&r, opts);
+                }
+            }
+            Ok(r) if opts.plain_text => print_plain(&r, opts),
+            Ok(r) if opts.brief => print_brief(&r, opts),
+            Ok(r) if opts.summary => print_summary(&r, opts),
+            Ok(r) if opts.tui => {
+                if let Err(e) = tui::run(&r, opts) {
+                    eprintln!("{e}");
+                }
+            }
+            Ok(r) => print_report(&r, opts),
+            Err(e) => eprintln!("{e}"),
+        });
+    }
+    if opts.benchmark {
+        print_timings();
+    }
+
+    if let Some(path) = &opts.export_ics
+        && let Err(e) = write_ics(path, &events)
+    {
+        eprintln!("Warning: could not write calendar {path}: {e}");
+    }
+
+    if let Some(path) = &opts.output_json {
+        let doc = if opts.file.is_some() { serde_json::Value::Array(json_file) } else { json_file.pop().unwrap_or_default() };
+        let text = serde_json::to_string_pretty(&doc).unwrap_or_default();
+        if let Err(e) = std::fs::write(path, text + "\n") {
+            eprintln!("Warning: could not write JSON {path}: {e}");
+        }
+    }
+
+    if let Some(path) = &opts.emit_metrics
+        && let Err(e) = write_metrics(path, &metrics)
+    {
+        eprintln!("Warning: could not write metrics {path}: {e}");
+    }
+
+    if let Some(path) = &opts.log {
+        let record = serde_json::json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "locations": resolved,
+            "requests": *client.exchanges(),
+        });
+        if let Err(e) = append_line(path, &record.to_string()) {
+            eprintln!("Warning: could not write log {path}: {e}");
+        }
+    }
+
+    if opts.format == Format::Json {
+        let out = if opts.file.is_some() { serde_json::Value::Array(json) } else { json.pop().unwrap_or_default() };
+        if !out.is_null() {
+            let text = if opts.compact_json || !std::io::stdout().is_terminal() {
+                serde_json::to_string(&out)
+            } else {
+                serde_json::to_string_pretty(&out)
+            };
+            emit_raw(&text.unwrap_or_default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOMINATIM: &str = include_str!("../tests/fixtures/nominatim_berlin.json");
+    const BRIGHTSKY: &str = include_str!("../tests/fixtures/brightsky_weather.json");
+
+    struct MockClient {
+        responses: Vec<(&'static str, Result<String, u16>)>,
+    }
+
+    impl MockClient {
+        fn new(responses: Vec<(&'static str, Result<String, u16>)>) -> MockClient {
+            MockClient { responses }
+        }
+    }
+
+    impl HttpClient for MockClient {
+        fn get(&self, url: &str) -> Result<String, FetchError> {
+            let (_, resp) = self
+                .responses
+                .iter()
+                .find(|(pattern, _)| url.contains(pattern))
+                .unwrap_or_else(|| panic!("unexpected request: {url}"));
+            resp.clone().map_err(|status| FetchError { status: Some(status), message: format!("HTTP {status}") })
+        }
+    }
+
+    fn entries(json: &str) -> Vec<WeatherEntry> {
+        serde_json::from_str::<Response>(json).unwrap().weather
+    }
+
+    #[test]
+    fn geocode_reads_first_result() {
+        let client = MockClient::new(vec![("nominatim", Ok(NOMINATIM.to_string()))]);
+        let (lat, lon, name) = geocode(&client, "Berlin", false).unwrap().unwrap();
+        assert_eq!((lat, lon, name.as_str()), (52.5173885, 13.3951309, "Berlin"));
+    }
+
+    #[test]
+    fn geocode_miss_on_empty_result() {
+        let client = MockClient::new(vec![("nominatim", Ok("[]".to_string()))]);
+        assert!(geocode(&client, "Nowhere", false).unwrap().is_none());
+    }
+
+    #[test]
+    fn geocode_uses_structured_postal_query() {
+        assert_eq!(postal_code("DE 10115"), Some((Some("DE"), "10115")));
+        assert_eq!(postal_code("10115"), Some((None, "10115")));
+        assert_eq!(postal_code("Berlin"), None);
+        let client = MockClient::new(vec![
+            ("postalcode=10115&format=json&limit=1&country=DE", Ok(NOMINATIM.to_string())),
+            ("q=", Ok("[]".to_string())),
+        ]);
+        assert!(geocode(&client, "DE 10115", false).unwrap().is_some());
+    }
+
+    #[test]
+    fn strips_ansi_sequences() {
+        assert_eq!(strip_ansi(&format!("{BOLD}{CYAN}Berlin{RESET} 12°")), "Berlin 12°");
+        assert_eq!(strip_ansi("\x1b]8;;https://brightsky.dev\x1b\\link\x1b]8;;\x07 \x1b[2J\x1b[H\x1b7ok"), "link ok");
+    }
+
+    #[test]
+    fn truncates_by_visible_width() {
+        let line = format!("  {CYAN}Today{RESET} ☀️ 12°");
+        assert_eq!(display_width(&line), 14);
+        assert_eq!(strip_ansi(&truncate(&line, 10)), "  Today ☀️");
+        assert_eq!(strip_ansi(&truncate(&line, 4)), "  To");
+    }
+
+    #[test]
+    fn encodes_special_characters_in_city() {
+        assert_eq!(encode_query("Washington, D.C."), "Washington%2C%20D.C.");
+        assert_eq!(encode_query("? Saint-Jean-sur-Richelieu ?"), "Saint-Jean-sur-Richelieu");
+        assert_eq!(encode_query("A&B #1/2"), "A%26B%20%231%2F2");
+        assert_eq!(encode_query("Köln"), "K%C3%B6ln");
+    }
+
+    #[test]
+    fn locates_by_ip() {
+        let client = MockClient::new(vec![(
+            "ipapi.co",
+            Ok(r#"{"city": "Berlin", "latitude": 52.52, "longitude": 13.4}"#.to_string()),
+        )]);
+        assert_eq!(locate_by_ip(&client).unwrap(), (52.52, 13.4, "Berlin".to_string()));
+        let client = MockClient::new(vec![("ipapi.co", Ok(r#"{"error": true, "reason": "RateLimited"}"#.to_string()))]);
+        assert_eq!(locate_by_ip(&client).unwrap_err(), "Could not locate you by IP: RateLimited");
+    }
+
+    #[test]
+    fn geocode_drops_trailing_components() {
+        let client = MockClient::new(vec![
+            ("q=Berlin%2C%20Germany%2C%20Europe&", Ok("[]".to_string())),
+            ("q=Berlin%2C%20Germany&", Ok("[]".to_string())),
+            ("q=Berlin&", Ok(NOMINATIM.to_string())),
+        ]);
+        assert!(geocode(&client, "Berlin, Germany, Europe", false).unwrap().is_some());
+    }
+
+    #[test]
+    fn geocode_accepts_numeric_coordinates() {
+        let body = r#"[{"lat": 52.5173885, "lon": 13.3951309, "display_name": "Berlin, Deutschland"}]"#;
+        let client = MockClient::new(vec![("q=Berlin", Ok(body.to_string()))]);
+        let (lat, lon, name) = geocode(&client, "Berlin", false).unwrap().unwrap();
+        assert_eq!((lat, lon, name.as_str()), (52.5173885, 13.3951309, "Berlin"));
+    }
+
+    #[test]
+    fn suggests_close_matches() {
+        assert_eq!(levenshtein("berln", "berlin"), 1);
+        let results = r#"[
+            {"lat": "52.5", "lon": "13.4", "display_name": "Berlin, Deutschland"},
+            {"lat": "60.4", "lon": "5.3", "display_name": "Bergen, Norge"}
+        ]"#;
+        let client = MockClient::new(vec![("q=ber&", Ok(results.to_string())), ("q=bxr&", Ok(results.to_string()))]);
+        assert_eq!(suggest(&client, "Berln").as_deref(), Some("Berlin"));
+        assert_eq!(suggest(&client, "Bxrxyzq"), None);
+    }
+
+    #[test]
+    fn offline_miss_makes_no_requests() {
+        let opts = Options { offline: true, refresh_geocode: true, ..Options::default() };
+        let err = resolve(&MockClient::new(vec![]), "Berln", &opts).unwrap_err();
+        assert_eq!(err, "Could not find city: Berln");
+    }
+
+    #[test]
+    fn geocode_falls_back_to_freeform() {
+        let client = MockClient::new(vec![
+            ("postalcode=", Ok("[]".to_string())),
+            ("q=10115", Ok(NOMINATIM.to_string())),
+        ]);
+        assert!(geocode(&client, "10115", false).unwrap().is_some());
+    }
+
+    #[test]
+    fn geocode_surfaces_service_errors() {
+        let client = MockClient::new(vec![("nominatim", Ok(r#"{"error": "Rate limit exceeded"}"#.to_string()))]);
+        let err = geocode(&client, "Berlin", false).unwrap_err();
+        assert_eq!(err, "Geocoding service error: Rate limit exceeded");
+    }
+
+    #[test]
+    fn forecast_widens_radius_when_no_station() {
+        let client = MockClient::new(vec![
+            ("max_dist", Ok(BRIGHTSKY.to_string())),
+            ("weather", Err(404)),
+        ]);
+        let (body, widened) = fetch_forecast(&client, "https://api.brightsky.dev/weather?lat=0&lon=0").unwrap();
+        assert!(widened);
+        assert!(has_weather(&body));
+    }
+
+    #[test]
+    fn fills_truncated_window() {
+        let first = r#"{"weather": [{"timestamp": "2024-10-14T10:00:00+00:00", "temperature": 12.0}]}"#;
+        let rest = r#"{"weather": [{"timestamp": "2024-10-14T11:00:00+00:00", "temperature": 13.0},
+            {"timestamp": "2024-10-14T12:00:00+00:00", "temperature": 14.0}]}"#;
+        let client = MockClient::new(vec![("/weather?", Ok(rest.to_string()))]);
+        let end = DateTime::parse_from_rfc3339("2024-10-14T12:30:00+00:00").unwrap().with_timezone(&Local);
+        let body = fill_window(&client, 52.52, 13.4, first.to_string(), end, false, &Options::default());
+        assert_eq!(entries(&body).len(), 3);
+        // A station only found at the wider radius stays reachable for the follow-ups
+        let client = MockClient::new(vec![("max_dist=200000", Ok(rest.to_string())), ("/weather?", Ok(r#"{"weather": []}"#.to_string()))]);
+        let body = fill_window(&client, 52.52, 13.4, first.to_string(), end, true, &Options::default());
+        assert_eq!(entries(&body).len(), 3);
+    }
+
+    #[test]
+    fn forecast_keeps_other_errors() {
+        let client = MockClient::new(vec![("weather", Err(500))]);
+        let err = fetch_forecast(&client, "https://api.brightsky.dev/weather").unwrap_err();
+        assert_eq!(err.status, Some(500));
+    }
+
+    #[test]
+    fn parses_alerts() {
+        let body = r#"{"alerts": [{"id": 1, "severity": "severe", "headline_en": "Official WARNING of GALE",
+            "headline_de": "Amtliche UNWETTERWARNUNG vor STURMBÖEN", "onset": "2024-10-14T12:00:00+02:00",
+            "expires": "2024-10-14T20:00:00+02:00", "event_en": "gale"}], "location": {"id": 1}}"#;
+        let client = MockClient::new(vec![("alerts", Ok(body.to_string()))]);
+        let alerts = fetch_alerts(&client, 52.5, 13.4).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, "severe");
+        assert_eq!(alert_time(alerts[0].onset.as_deref().unwrap(), &Options::default()).unwrap(), "Mon 12:00");
+    }
+
+    #[test]
+    fn aggregates_fixture_by_day() {
+        let days = aggregate(&entries(BRIGHTSKY), "2024-10-14", &Options::default());
+        assert_eq!(days.len(), 2);
+        let (day, d) = &days[0];
+        assert_eq!(day, "2024-10-14");
+        assert_eq!((d.lo, d.hi, d.max_rp), (8.4, 15.0, 80.0));
+        assert_eq!(d.conds, ["fog", "rain"]);
+        assert_eq!(breakdown(d, Lang::En), "dry 7h, rain 3h, fog 2h");
+        assert_eq!(d.hours.len(), 12);
+        assert!(days[1].1.hours.is_empty());
+    }
+
+    #[test]
+    fn clamps_rain_probability() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+02:00", "temperature": 12.0, "precipitation_probability": 150.0, "condition": "rain"},
+            {"timestamp": "2024-10-14T11:00:00+02:00", "temperature": 12.0, "precipitation_probability": -5.0, "condition": "rain"}
+        ]}"#;
+        let opts = Options { timezone: Some(chrono_tz::UTC), ..Options::default() };
+        let days = aggregate(&entries(json), "2024-10-14", &opts);
+        let text = strip_ansi(&capture(|| print_hourly(&days[0].1, &opts)));
+        let rows: Vec<&str> = text.lines().filter(|l| l.trim_start().starts_with("0")).collect();
+        assert_eq!(rows.len(), 2, "{text}");
+        assert!(rows[0].contains(" 100%") && !rows[0].contains("150"), "{text}");
+        assert!(rows[1].contains("   0%") && !rows[1].contains('-'), "{text}");
+    }
+
+    #[test]
+    fn detects_fields_missing_everywhere() {
+        assert!(missing_fields(&entries(BRIGHTSKY)).is_empty());
+        let json = r#"{"weather": [{"timestamp": "2024-10-14T10:00:00+02:00", "temperature": 12.0,
+            "precip_probability": 20.0, "condition": "dry", "wind_speed": 5.0, "precipitation": 0.0}]}"#;
+        assert_eq!(missing_fields(&entries(json)), ["precipitation_probability"]);
+    }
+
+    #[test]
+    fn converts_to_requested_timezone() {
+        let opts = Options { timezone: Some(chrono_tz::America::New_York), ..Default::default() };
+        let days = aggregate(&entries(BRIGHTSKY), "2024-10-13", &opts);
+        assert_eq!(days[0].0, "2024-10-13");
+        assert_eq!(days[0].1.hours[0].time, "18:00");
+    }
+
+    #[test]
+    fn treats_null_condition_as_dry() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+02:00", "temperature": 12.0, "condition": "rain"},
+            {"timestamp": "2024-10-14T11:00:00+02:00", "temperature": 13.0, "condition": null},
+            {"timestamp": "2024-10-14T12:00:00+02:00", "temperature": 14.0, "condition": "fog"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        let conds: Vec<&str> = days[0].1.hours.iter().map(|h| h.cond.as_str()).collect();
+        assert_eq!(conds, ["rain", "dry", "fog"]);
+        assert_eq!(days[0].1.conds, ["rain", "fog"]);
+    }
+
+    #[test]
+    fn buckets_irregular_timestamps() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T14:30:00+02:00", "temperature": 12.0, "condition": "dry"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        assert_eq!(days[0].1.hours[0].time, "14:00");
+        let exact = Options { exact_minutes: true, ..Default::default() };
+        let days = aggregate(&entries(json), "2024-10-14", &exact);
+        assert_eq!(days[0].1.hours[0].time, "14:30");
+        let pair = r#"{"weather": [
+            {"timestamp": "2024-10-14T14:00:00+02:00", "temperature": 11.0, "condition": "dry"},
+            {"timestamp": "2024-10-14T14:30:00+02:00", "temperature": 12.0, "condition": "dry"}
+        ]}"#;
+        let days = aggregate(&entries(pair), "2024-10-14", &Options::default());
+        let times: Vec<&str> = days[0].1.hours.iter().map(|h| h.time.as_str()).collect();
+        assert_eq!(times, ["14:00"]);
+        let days = aggregate(&entries(pair), "2024-10-14", &exact);
+        assert_eq!(days[0].1.hours.len(), 2);
+    }
+
+    #[test]
+    fn skips_entries_without_temperature() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+02:00", "temperature": 12.0, "condition": "dry"},
+            {"timestamp": "2024-10-14T11:00:00+02:00", "temperature": null, "condition": "rain"},
+            {"timestamp": "2024-10-14T12:00:00+02:00", "condition": "rain"},
+            {"timestamp": "2024-10-14T13:00:00+02:00", "temperature": 14.5, "condition": "dry"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        let times: Vec<&str> = days[0].1.hours.iter().map(|h| h.time.as_str()).collect();
+        assert_eq!(times, ["10:00", "13:00"]);
+        assert_eq!((days[0].1.lo, days[0].1.hi), (12.0, 14.5));
+    }
+
+    #[test]
+    fn malformed_config_falls_back_to_defaults() {
+        let path = std::path::Path::new("/tmp/config.toml");
+        let (cfg, warning) = config::parse(path, "city = \"Berlin\"\nlang = de\n");
+        assert_eq!(cfg, config::Config::default());
+        assert_eq!(warning.unwrap().split(':').next(), Some("Config error in /tmp/config.toml"));
+        let (cfg, warning) = config::parse(path, "city = \"Berlin\"\n");
+        assert_eq!(cfg.city.as_deref(), Some("Berlin"));
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn default_coords_only_without_city() {
+        let (mut cfg, warning) = config::parse(std::path::Path::new("/tmp/config.toml"), "default_coords = [52.52, 13.4]\n");
+        assert!(warning.is_none());
+        assert_eq!(location(&[], &cfg), "52.52,13.4");
+        assert_eq!(location(&["48.1,11.6".to_string()], &cfg), "48.1,11.6");
+        assert_eq!(location(&["New".to_string(), "York".to_string()], &cfg), "New York");
+        cfg.city = Some("Hamburg".to_string());
+        assert_eq!(location(&[], &cfg), "Hamburg");
+    }
+
+    #[test]
+    fn config_icon_overrides() {
+        let (cfg, warning) = config::parse(std::path::Path::new("/tmp/config.toml"), "[icons]\nrain = \"\\ue318\"\n");
+        assert_eq!(cfg.icons.get("rain").map(String::as_str), Some("\u{e318}"));
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn parses_coords_with_altitude() {
+        let c = parse_coords("52.5,13.4,34").unwrap();
+        assert_eq!((c.lat, c.lon, c.alt, c.label), (52.5, 13.4, Some(34.0), None));
+        let c = parse_coords("52.5, 13.4, 34m, Home").unwrap();
+        assert_eq!((c.alt, c.label.as_deref()), (Some(34.0), Some("Home")));
+        let c = parse_coords("52.5,13.4,Berlin, Mitte").unwrap();
+        assert_eq!((c.alt, c.label.as_deref()), (None, Some("Berlin, Mitte")));
+    }
+
+    #[test]
+    fn picks_icon_from_cloud_cover() {
+        assert_eq!(sky_icon("dry", Some(10.0)), "☀️");
+        assert_eq!(sky_icon("dry", Some(40.0)), "🌤️");
+        assert_eq!(sky_icon("dry", Some(95.0)), "☁️");
+        assert_eq!(sky_icon("dry", None), "☀️");
+        assert_eq!(sky_icon("rain", Some(95.0)), "🌧️");
+    }
+
+    #[test]
+    fn comfort_uses_available_inputs() {
+        assert_eq!(comfort(20.0, None, None), (20.0, "comfortable"));
+        let (at, label) = comfort(20.0, Some(50.0), Some(36.0));
+        assert!((at - 12.85).abs() < 0.05, "{at}");
+        assert_eq!(label, "cool");
+    }
+
+    #[test]
+    fn scales_rain_bar_to_maximum() {
+        assert_eq!(rain_bar(20.0, 20.0), "████████");
+        assert_eq!(rain_bar(10.0, 20.0), "████    ");
+        assert_eq!(rain_bar(1.0, 20.0), "▍       ");
+        assert_eq!(rain_bar(0.0, 0.0), "        ");
+    }
+
+    #[test]
+    fn widens_temperature_column_for_extremes() {
+        assert_eq!(temp_width([12.3, -4.0].into_iter()), 5);
+        assert_eq!(temp_width([-12.3, 8.0].into_iter()), 5);
+        assert_eq!(temp_width([-100.0, 8.0].into_iter()), 6);
+    }
+
+    #[test]
+    fn computes_sunrise_and_sunset() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let (rise, set) = sun_times(date, 52.52, 13.40).unwrap();
+        let minutes = |t: NaiveDateTime| i64::from(t.hour() * 60 + t.minute());
+        assert!((minutes(rise) - (2 * 60 + 43)).abs() <= 5, "{rise}");
+        assert!((minutes(set) - (19 * 60 + 33)).abs() <= 5, "{set}");
+        assert!(sun_times(date, 80.0, 15.0).is_none());
+    }
+
+    #[test]
+    fn explains_temp_in_display_units() {
+        assert_eq!(explain_temp(10.6, &Options::default()), "temp 10.6 °C→GREEN band 10-20 °C");
+        let opts = Options { units: Units::Imperial, ..Default::default() };
+        assert_eq!(explain_temp(10.6, &opts), "temp 51.1 °F→GREEN band 50-68 °F");
+    }
+
+    #[test]
+    fn snaps_temperatures_to_increment() {
+        let opts = Options { round_temp_to: Some(0.5), ..Default::default() };
+        assert_eq!((snap(12.3, &opts), snap(12.2, &opts), snap(-3.8, &opts)), (12.5, 12.0, -4.0));
+        assert_eq!(snap(12.3, &Options::default()), 12.3);
+    }
+
+    #[test]
+    fn maps_hours_to_dayparts() {
+        let parts: Vec<&str> = [0, 5, 6, 11, 12, 17, 18, 23].into_iter().map(daypart).collect();
+        assert_eq!(parts, ["night", "night", "morning", "morning", "afternoon", "afternoon", "evening", "evening"]);
+    }
+
+    #[test]
+    fn reports_frost_runs() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-15T02:00:00+00:00", "temperature": 1.0},
+            {"timestamp": "2024-10-15T03:00:00+00:00", "temperature": -0.5},
+            {"timestamp": "2024-10-15T04:00:00+00:00", "temperature": -1.2},
+            {"timestamp": "2024-10-15T06:00:00+00:00", "temperature": -0.1},
+            {"timestamp": "2024-10-15T07:00:00+00:00", "temperature": 2.0}
+        ]}"#;
+        let opts = Options { min_temp: Some(0.0), ..Default::default() };
+        assert_eq!(temp_warnings(&entries(json), &opts), ["Frost expected Tue 03:00–06:00"]);
+        let opts = Options { min_temp: Some(1.5), ..Default::default() };
+        assert_eq!(temp_warnings(&entries(json), &opts), ["Below 1.5° expected Tue 02:00–06:00"]);
+    }
+
+    #[test]
+    fn collapses_identical_hours() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T14:00:00+00:00", "temperature": 18.0, "precipitation_probability": 0, "condition": "dry"},
+            {"timestamp": "2024-10-14T15:00:00+00:00", "temperature": 18.2, "precipitation_probability": 10, "condition": "dry"},
+            {"timestamp": "2024-10-14T16:00:00+00:00", "temperature": 17.9, "precipitation_probability": 5, "condition": "dry"},
+            {"timestamp": "2024-10-14T17:00:00+00:00", "temperature": 16.0, "precipitation_probability": 5, "condition": "dry"},
+            {"timestamp": "2024-10-14T18:00:00+00:00", "temperature": 16.0, "precipitation_probability": 60, "condition": "rain"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        assert_eq!(collapse_runs(&days[0].1.hours, None), [(0, 2), (3, 3), (4, 4)]);
+    }
+
+    #[test]
+    fn keeps_sun_and_moon_rows_apart() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T19:00:00+00:00", "temperature": 12.0, "precipitation_probability": 0, "condition": "dry"},
+            {"timestamp": "2024-10-14T20:00:00+00:00", "temperature": 12.0, "precipitation_probability": 0, "condition": "dry"},
+            {"timestamp": "2024-10-14T21:00:00+00:00", "temperature": 12.0, "precipitation_probability": 0, "condition": "dry"},
+            {"timestamp": "2024-10-14T22:00:00+00:00", "temperature": 12.0, "precipitation_probability": 0, "condition": "dry"}
+        ]}"#;
+        let opts = Options { timezone: Some(chrono_tz::UTC), ..Options::default() };
+        let days = aggregate(&entries(json), "2024-10-14", &opts);
+        assert_eq!(collapse_runs(&days[0].1.hours, None), [(0, 1), (2, 3)]);
+    }
+
+    #[test]
+    fn labels_hours_across_spring_forward() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-03-30T23:00:00+00:00", "temperature": 4.0},
+            {"timestamp": "2024-03-31T00:00:00+00:00", "temperature": 4.0},
+            {"timestamp": "2024-03-31T01:00:00+00:00", "temperature": 3.5},
+            {"timestamp": "2024-03-31T02:00:00+00:00", "temperature": 3.0}
+        ]}"#;
+        let opts = Options { timezone: Some(chrono_tz::Europe::Berlin), ..Default::default() };
+        let days = aggregate(&entries(json), "2024-03-31", &opts);
+        let times: Vec<&str> = days[0].1.hours.iter().map(|h| h.time.as_str()).collect();
+        assert_eq!(times, ["00:00", "01:00", "03:00", "04:00"]);
+        assert_eq!(days[0].1.dst_note.as_deref(), Some("Clocks change at 03:00 (UTC+1 → UTC+2)"));
+    }
+
+    #[test]
+    fn reports_contributing_stations() {
+        let r = build_report(BRIGHTSKY, f64::NAN, f64::NAN, "Berlin", None, &Options::default()).unwrap();
+        let json = json_output(&r, &Options::default());
+        assert_eq!(json["stations"][0]["dwd_station_id"], "10385");
+        assert_eq!(json["stations"][0]["station_name"], "BERLIN-SCHOENEFELD");
+        assert_eq!(json["location"]["lat"], 52.3807);
+    }
+
+    fn render(opts: &Options, print: fn(&Report, &Options)) -> String {
+        let r = build_report(BRIGHTSKY, f64::NAN, f64::NAN, "Berlin", None, opts).unwrap();
+        strip_ansi(&capture(|| print(&r, opts)))
+    }
+
+    #[test]
+    fn snapshot_report() {
+        assert_eq!(render(&Options::default(), print_report), include_str!("../tests/fixtures/berlin_report.txt"));
+    }
+
+    #[test]
+    fn summary_honours_prob_as_fraction() {
+        let opts = Options { prob_as_fraction: true, ..Default::default() };
+        let text = render(&opts, print_summary);
+        assert!(text.contains(", 0.80 chance of rain."), "{text}");
+    }
+
+    #[test]
+    fn show_coords_skips_coordinate_names() {
+        let opts = Options { show_coords: true, ..Default::default() };
+        let r = build_report(BRIGHTSKY, 52.5, 13.4, "52.50, 13.40", None, &opts).unwrap();
+        let text = strip_ansi(&capture(|| print_report(&r, &opts)));
+        assert!(text.contains("52.50, 13.40") && !text.contains("(52.50, 13.40)"), "{text}");
+        let r = build_report(BRIGHTSKY, 52.5, 13.4, "Berlin", None, &opts).unwrap();
+        assert!(strip_ansi(&capture(|| print_report(&r, &opts))).contains("Berlin (52.50, 13.40)"));
+    }
+
+    #[test]
+    fn snapshot_brief() {
+        let opts = Options { lang: Lang::De, decimal_comma: true, ..Default::default() };
+        assert_eq!(render(&opts, print_brief), "Mo 🌧️ 8/15 80%\nDi ⛈️ 8/13 75%\n");
+    }
+
+    #[test]
+    fn short_wind_cells_share_a_width() {
+        let widths: Vec<usize> = [WindScale::Kmh, WindScale::Ms, WindScale::Beaufort]
+            .into_iter()
+            .map(|scale| fmt_wind(20.0, scale, false).len())
+            .collect();
+        assert_eq!(widths, [8, 8, 8]);
+    }
+
+    #[test]
+    fn localizes_hourly_headers() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+00:00", "temperature": 12.0, "precipitation": 1.0}
+        ]}"#;
+        let opts = Options { timezone: Some(chrono_tz::UTC), lang: Lang::De, ..Options::default() };
+        let days = aggregate(&entries(json), "2024-10-14", &opts);
+        let text = strip_ansi(&capture(|| print_hourly(&days[0].1, &opts)));
+        assert!(text.contains("Zeit") && text.contains("Stärke") && text.contains("leicht"), "{text}");
+        assert!(!text.contains("Time") && !text.contains("light"), "{text}");
+    }
+
+    #[test]
+    fn pads_missing_wind_cells() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+00:00", "temperature": 12.0, "wind_speed": 10.0, "precipitation": 0.5},
+            {"timestamp": "2024-10-14T11:00:00+00:00", "temperature": 13.0, "precipitation": 0.5},
+            {"timestamp": "2024-10-14T12:00:00+00:00", "temperature": 14.0, "wind_speed": 12.0, "precipitation": 0.5}
+        ]}"#;
+        let opts = Options { timezone: Some(chrono_tz::UTC), accum: true, ..Options::default() };
+        let days = aggregate(&entries(json), "2024-10-14", &opts);
+        let text = strip_ansi(&capture(|| print_hourly(&days[0].1, &opts)));
+        let widths: Vec<usize> = text.lines().filter(|l| l.ends_with(" mm")).map(display_width).collect();
+        assert_eq!(widths.len(), 3, "{text}");
+        assert!(widths.iter().all(|w| *w == widths[0]), "{text}");
+    }
+
+    #[test]
+    fn clamps_requested_days() {
+        assert_eq!(window_days(5, &Options::default()), 5);
+        assert_eq!(window_days(90, &Options::default()), MAX_DAYS_FROM_NOW);
+        assert_eq!(window_days(90, &Options { max_days_from_now: Some(30), ..Options::default() }), 30);
+    }
+
+    #[test]
+    fn wraps_hour_range_past_midnight() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T09:00:00+00:00", "temperature": 12.0},
+            {"timestamp": "2024-10-14T22:00:00+00:00", "temperature": 9.0},
+            {"timestamp": "2024-10-14T23:00:00+00:00", "temperature": 8.0},
+            {"timestamp": "2024-10-15T05:00:00+00:00", "temperature": 6.0},
+            {"timestamp": "2024-10-15T07:00:00+00:00", "temperature": 7.0}
+        ]}"#;
+        let opts = Options { timezone: Some(chrono_tz::UTC), hour_range: Some((22, 6)), ..Options::default() };
+        let mut days = aggregate(&entries(json), "2024-10-14", &opts);
+        window_hours(&mut days, (22, 6));
+        let times: Vec<&str> = days[0].1.hours.iter().map(|h| h.time.as_str()).collect();
+        assert_eq!(times, ["22:00", "23:00", "05:00"]);
+    }
+
+    #[test]
+    fn explains_night_icon() {
+        let mut h = Hour { time: "23:00".to_string(), temp: 8.0, rp: 0.0, cond: "dry".to_string(), wind: None, precip: None, cloud: Some(10.0), visibility: None, storm: None, utc: None };
+        assert_eq!(hour_icon(&h, None), "🌙");
+        assert_eq!(explain_hour_icon(&h, None, Lang::En), "dry at night→moon");
+        h.time = "13:00".to_string();
+        h.cloud = Some(70.0);
+        assert_eq!(explain_hour_icon(&h, None, Lang::En), "dry, 70% cloud cover→⛅");
+    }
+
+    #[test]
+    fn exports_ics_events() {
+        let r = build_report(include_str!("../tests/fixtures/brightsky_weather.json"), 52.52, 13.4, "Berlin, Mitte", None, &Options::default()).unwrap();
+        let events = ics_events(&r, "20241014T000000Z", &Options::default());
+        assert_eq!(events.len(), r.days.len());
+        assert!(events[0].contains("\r\nSUMMARY:🌧️ 15°/8° · 80% rain\r\n"), "{}", events[0]);
+        assert!(events[0].contains("LOCATION:Berlin\\, Mitte"));
+        let name = "Schönwalde-Glien, Landkreis Havelland, Brandenburg, Deutschland, Europa";
+        let r = build_report(BRIGHTSKY, 52.52, 13.4, name, None, &Options::default()).unwrap();
+        let event = &ics_events(&r, "20241014T000000Z", &Options::default())[0];
+        assert!(event.split("\r\n").all(|l| l.len() <= 75), "{event}");
+        assert!(event.replace("\r\n ", "").contains(&format!("LOCATION:{}", ics_text(name))));
+    }
+
+    #[test]
+    fn decodes_plus_codes() {
+        let (lat, lon) = decode_plus_code("8FVC9G8F+6X").unwrap();
+        assert!((lat - 47.365562).abs() < 1e-4 && (lon - 8.524968).abs() < 1e-4, "{lat} {lon}");
+        let (lat, lon) = decode_plus_code("9F4MGC22+").unwrap();
+        assert!((lat - 52.50125).abs() < 1e-4 && (lon - 13.40125).abs() < 1e-4, "{lat} {lon}");
+        assert_eq!(decode_plus_code("GC22+22"), None);
+        for bad in ["XFVC9G8F+6X", "8XVC9G8F+6X", "00000000+", "8F00G000+", "8FVC9G00+6X", "8FVC9G8F+6X+", "8FVC9G8F+6XXXXXXX"] {
+            assert_eq!(decode_plus_code(bad), None, "{bad}");
+        }
+        assert!(is_what3words("///filled.count.soap") && !is_what3words("Berlin"));
+    }
+
+    #[test]
+    fn renders_malformed_day_key() {
+        let json = r#"{"weather": [{"timestamp": "2024-10-14T10:00:00+00:00", "temperature": 12.0}]}"#;
+        let mut days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        days[0].0 = "14.10.".to_string();
+        let today = NaiveDate::from_ymd_opt(2024, 10, 14).unwrap();
+        let text = strip_ansi(&capture(|| print_cards(&days, today, &Options::default())));
+        assert!(text.contains("  14.10.    "), "{text}");
+    }
+
+    #[test]
+    fn normalizes_condition_case() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+00:00", "temperature": 12.0, "condition": "Rain"},
+            {"timestamp": "2024-10-14T11:00:00+00:00", "temperature": 12.0, "condition": "RAIN"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        assert_eq!(days[0].1.conds, ["rain"]);
+        assert_eq!(pick_icon(&days[0].1.conds, None), "🌧️");
+        assert_eq!(icon("Thunderstorm"), "⛈️");
+    }
+
+    #[test]
+    fn sizes_labels_per_language() {
+        let today = NaiveDate::from_ymd_opt(2024, 10, 14).unwrap();
+        assert_eq!(label_width(today, &Options::default()), 10);
+        assert_eq!(label_width(today, &Options { lang: Lang::De, ..Options::default() }), 9);
+        assert_eq!(label_width(today, &Options { layout: LayoutStyle::Wide, ..Options::default() }), 18);
+    }
+
+    #[test]
+    fn overrides_displayed_weather() {
+        let opts = Options { condition_override: Some("snow".to_string()), temp_override: Some(-3.0), ..Options::default() };
+        let r = build_report(include_str!("../tests/fixtures/brightsky_weather.json"), 52.52, 13.4, "Berlin", None, &opts).unwrap();
+        assert!(r.days.iter().all(|(_, d)| d.conds == ["snow"] && d.hi == -3.0 && d.lo == -3.0));
+    }
+
+    #[test]
+    fn takes_median() {
+        assert_eq!(median(&mut [9.0, 2.0, 30.0]), 9.0);
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 30.0]), 3.5);
+        assert!(median(&mut []).is_nan());
+    }
+
+    #[test]
+    fn finds_rain_transitions() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+00:00", "temperature": 12.0, "condition": "dry"},
+            {"timestamp": "2024-10-14T12:00:00+00:00", "temperature": 12.0, "condition": "rain"},
+            {"timestamp": "2024-10-14T13:00:00+00:00", "temperature": 12.0, "condition": "thunderstorm"},
+            {"timestamp": "2024-10-14T15:00:00+00:00", "temperature": 12.0, "condition": "cloudy"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        assert_eq!(transitions(&days[0].1.hours, Lang::En), ["rain starting around 12:00", "clearing up after 15:00"]);
+    }
+
+    #[test]
+    fn drops_duplicate_timestamps() {
+        let json = r#"{"weather": [
+            {"timestamp": "2024-10-14T10:00:00+02:00", "temperature": 12.0, "condition": "dry"},
+            {"timestamp": "2024-10-14T08:00:00+00:00", "temperature": 11.0, "condition": "rain"},
+            {"timestamp": "2024-10-14T11:00:00+02:00", "temperature": 13.0, "condition": "dry"}
+        ]}"#;
+        let days = aggregate(&entries(json), "2024-10-14", &Options::default());
+        assert_eq!(days[0].1.lo, 12.0);
+        assert!(days[0].1.conds.is_empty());
+        let text = strip_ansi(&capture(|| print_hourly(&days[0].1, &Options::default())));
+        assert_eq!(text.lines().filter(|l| l.trim_start().starts_with("10:00")).count(), 1, "{text}");
+        assert_eq!(text.lines().filter(|l| l.trim_start().starts_with("11:00")).count(), 1, "{text}");
+    }
 }
@@ -0,0 +1,59 @@
+use std::io::{IsTerminal, Write, stdout};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{Event, KeyCode, KeyEventKind, read};
+use crossterm::terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
+use crossterm::{execute, queue};
+
+use crate::{BOLD, CYAN, DIM, Options, RESET, Report, capture, print_cards, print_hourly};
+
+pub fn run(r: &Report, opts: &Options) -> std::io::Result<()> {
+    if r.days.is_empty() {
+        return Ok(());
+    }
+    if !stdout().is_terminal() {
+        return Err(std::io::Error::other("--tui needs a terminal"));
+    }
+    let cards: Vec<String> = r.days.iter()
+        .map(|day| {
+            let out = capture(|| print_cards(std::slice::from_ref(day), r.today, opts));
+            out.lines().skip(2).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("\r\n")
+        })
+        .collect();
+    let head = capture(|| print_cards(&[], r.today, opts));
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen, Hide)?;
+    let result = event_loop(r, opts, &head, &cards);
+    execute!(stdout(), Show, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    result
+}
+
+fn event_loop(r: &Report, opts: &Options, head: &str, cards: &[String]) -> std::io::Result<()> {
+    let mut selected = 0;
+    loop {
+        let mut out = stdout();
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+        write!(out, "\r\n  {BOLD}{CYAN}{}{RESET}  {DIM}↑/↓ select, q quit{RESET}\r\n", r.name)?;
+        write!(out, "{}", head.replace('\n', "\r\n"))?;
+        for (i, card) in cards.iter().enumerate() {
+            let marker = if i == selected { "▶" } else { " " };
+            write!(out, "{marker}{}\r\n", card.strip_prefix(' ').unwrap_or(card))?;
+        }
+        let hourly = capture(|| print_hourly(&r.days[selected].1, opts));
+        write!(out, "{}", hourly.replace('\n', "\r\n"))?;
+        out.flush()?;
+
+        if let Event::Key(key) = read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(cards.len() - 1),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}